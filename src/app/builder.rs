@@ -5,8 +5,12 @@ use fltk::{
         App,
         Sender,
         screen_size,
+        screen_count,
+        screen_work_area,
         event_dy,
+        event_key,
         event as get_last_event,
+        copy as copy_to_clipboard,
         MouseWheel
     },
     button::{
@@ -16,11 +20,17 @@ use fltk::{
     draw,
     enums::{
         Align,
+        CallbackTrigger,
         Color,
         Event,
-        FrameType
+        FrameType,
+        Key
     },
     frame::Frame,
+    input::{
+        Input,
+        FloatInput
+    },
     group::{
         Pack,
         PackType
@@ -39,8 +49,11 @@ use fltk::{
         DisplayExt,
         ButtonExt,
         ValuatorExt,
-        ImageExt
+        ImageExt,
+        InputExt,
+        MenuExt
     },
+    menu::Choice,
     misc::Progress,
     valuator::Slider,
     window::{
@@ -49,12 +62,24 @@ use fltk::{
     }
 };
 
+use std::{
+    cmp::{max, min},
+    path::Path,
+    rc::Rc,
+    cell::{Cell, RefCell}
+};
+
 use crate::{
-    utils::load_icon,
+    config::WindowGeometry,
+    utils::{load_icon, open_url, format_size, DryRunReport, RetryMode, SprBundleInfo, VerifyReport},
+    platform,
     Message,
     static_data
 };
-use super::{styles::*, state::ThreadSafeState};
+use super::{
+    styles::*,
+    strings::{get_strings, get_language, Language}
+};
 
 
 /// Builds a default fltk app
@@ -64,39 +89,77 @@ pub fn build_app() -> App {
 
 
 /// Builds an outer window
+/// Returns the title of the main window in the current language
+pub fn format_outer_win_title() -> String {
+    return format!("{} - {}", get_strings().win_title, crate::VERSION.unwrap_or(crate::DEF_VERSION));
+}
+
 /// This is the main window of the app
 /// Other windows get included into this
-pub fn build_outer_win(sender: Sender<Message>, app_state: &ThreadSafeState) -> DoubleWindow {
+pub fn build_outer_win(sender: Sender<Message>) -> DoubleWindow {
     let mut main_win = Window::default()
         .with_size(WIN_WIDTH, WIN_HEIGHT)
-        .with_label(&format!("{} - {}", WIN_TITLE, crate::VERSION.unwrap_or(crate::DEF_VERSION)))
+        .with_label(&format_outer_win_title())
         .center_screen();
     main_win.set_color(C_DDLC_PINK_IDLE);
 
     // This is so we can first show the abort screen, then quit
-    // when the user clicks X
+    // when the user clicks X, the app decides what to do
     main_win.set_callback(
-        {
-            let app_state = app_state.clone();
-            move |_| {
-                if get_last_event() == Event::Close {
-                    let abort_flag = app_state.lock().unwrap().get_abort_flag();
-                    match abort_flag {
-                        false => sender.send(Message::Abort),
-                        true => sender.send(Message::Close)
-                    };
-                };
+        move |_| {
+            if get_last_event() == Event::Close {
+                sender.send(Message::WindowClose);
+            };
+        }
+    );
+    // The license can be opened at any step
+    main_win.handle(
+        move |_, ev| {
+            if matches!(ev, Event::KeyDown | Event::Shortcut) && event_key() == Key::F1 {
+                sender.send(Message::ViewLicense);
+                return true;
             }
+            return false;
         }
     );
     // Set app icon
     load_icon(&mut main_win);
 
+    // The inner windows scale with the main one
+    main_win.make_resizable(true);
+    main_win.size_range(MIN_WIN_WIDTH, MIN_WIN_HEIGHT, 0, 0);
+
     main_win.end();
 
     return main_win;
 }
 
+/// Moves the main window to the saved place, fitted into the screen it overlaps the most,
+/// the window stays centered if the place isn't visible on any screen (e.g. it was disconnected)
+pub fn restore_outer_win_geometry(main_win: &mut DoubleWindow, geometry: &WindowGeometry) {
+    // The config might have anything, so no i32 math on it
+    let overlap = |(sx, sy, sw, sh): (i32, i32, i32, i32)| -> i64 {
+        let width = min(geometry.x as i64 + geometry.width as i64, (sx + sw) as i64) - max(geometry.x, sx) as i64;
+        let height = min(geometry.y as i64 + geometry.height as i64, (sy + sh) as i64) - max(geometry.y, sy) as i64;
+        return max(width, 0) * max(height, 0);
+    };
+    let screen = (0..screen_count())
+        .map(screen_work_area)
+        .map(|area| (overlap(area), area))
+        .filter(|(overlap, _)| *overlap > 0)
+        .max_by_key(|(overlap, _)| *overlap);
+    let (sx, sy, sw, sh) = match screen {
+        Some((_, area)) => area,
+        None => return
+    };
+
+    let width = geometry.width.clamp(MIN_WIN_WIDTH, max(sw, MIN_WIN_WIDTH));
+    let height = geometry.height.clamp(MIN_WIN_HEIGHT, max(sh, MIN_WIN_HEIGHT));
+    let x = geometry.x.clamp(sx, max(sx + sw - width, sx));
+    let y = geometry.y.clamp(sy, max(sy + sh - height, sy));
+    main_win.resize(x, y, width, height);
+}
+
 
 /// Builds an inner window
 /// Inner windows are included into the main window. User can switch between them
@@ -105,6 +168,8 @@ pub fn build_inner_win() -> DoubleWindow {
         .with_size(INNER_WIN_WIDTH, INNER_WIN_HEIGHT)
         .with_pos(WIN_PADDING, WIN_PADDING);
     inner_win.set_color(C_DDLC_WHITE_IDLE);
+    // The widgets scale with the window
+    inner_win.make_resizable(true);
 
     inner_win.end();
     inner_win.hide();
@@ -151,10 +216,13 @@ fn _handle_button(b: &mut Button, ev: Event) -> bool {
 fn __draw_button_widget(b: &mut dyn WidgetExt) {
     let (b_x, b_y, b_w, b_h) = (b.x(), b.y(), b.w(), b.h());
 
-    let (frame_color, bg_color, text_color) = match b.has_visible_focus() {
+    let (frame_color, bg_color, mut text_color) = match b.has_visible_focus() {
         true => (C_DDLC_PINK_ACT, C_DDLC_WHITE_ACT, C_DDLC_PEACH),
         false => (C_DDLC_PINK_IDLE, C_DDLC_WHITE_IDLE, C_BLACK)
     };
+    if !b.active() {
+        text_color = text_color.inactive();
+    }
 
     draw::draw_rect_fill(b_x, b_y, b_w, b_h, frame_color);
     draw::draw_rect_fill(b_x+BUT_PADDING, b_y+BUT_PADDING, b_w-BUT_PADDING*2, b_h-BUT_PADDING*2, bg_color);
@@ -251,7 +319,7 @@ fn _draw_check_button(b: &mut CheckButton) {
         draw::draw_rect_with_color(b_x+pad_inner, b_y+pad_inner, b_h-pad_inner*2, b_h-pad_inner*2, C_BLACK);
     }
 
-    draw::set_draw_color(txt_color);
+    draw::set_draw_color(if b.active() { txt_color } else { txt_color.inactive() });
     draw::set_font(BUT_FONT, BUT_FONT_SIZE);
     draw::draw_text2(&b.label(), b_x+b_h, b_y, b_w, b_h, b.align());
 
@@ -261,12 +329,19 @@ fn _draw_check_button(b: &mut CheckButton) {
 /// Builds a check button with the given parameters
 /// ev handler, and draw func are pre-defined
 fn _build_check_button(width: i32, height: i32, label: &str, sender: Sender<Message>, msg: Message, is_checked: bool) -> CheckButton {
+    let mut but = _build_check_button_base(width, height, label, is_checked);
+    but.emit(sender, msg);
+
+    return but;
+}
+
+/// Builds a check button without a callback, for the dialogs
+fn _build_check_button_base(width: i32, height: i32, label: &str, is_checked: bool) -> CheckButton {
     let mut but = CheckButton::default()
         .with_size(width, height)
         .with_label(label);
 
     but.visible_focus(false);
-    but.emit(sender, msg);
     but.handle(_handle_check_button);
     but.draw(_draw_check_button);
     but.set_checked(is_checked);
@@ -354,53 +429,106 @@ fn _build_welcome_win_inner_pack() -> Pack {
     return inner_pack;
 }
 
-fn _build_welcome_win_outer_pack() -> Pack {
-    const WIDTH: i32 = INNER_WIN_WIDTH-INNER_WIN_CONTENT_XPADDING*2;
+/// Builds a pack of buttons for the welcome window
+/// Example: <Abort> <Volume>      <Uninstall> <Continue>
+fn _build_welcome_win_pack(sender: Sender<Message>) -> Pack {
+    return _build_4but_pack(
+        sender,
+        (get_strings().but_uninstall_label, Message::Uninstall),
+        (get_strings().but_continue_label, Message::NextPage)
+    );
+}
 
-    let mut pack = Pack::default()
-        .with_size(WIDTH, BUT_HEIGHT)
-        .with_pos(INNER_WIN_CONTENT_XPADDING, INNER_WIN_HEIGHT-BUT_HEIGHT-BUT_PACK_YPADDING)
-        .with_align(Align::Center)
-        .with_type(PackType::Horizontal);
-    pack.set_spacing(
-        WIDTH - (BUT_WIDTH + BUT_SPACING + BUT_MUTE_WIDTH) - BUT_WIDTH
+/// Builds a dropdown to select the language of the app,
+/// the current language is selected
+fn _build_language_choice(sender: Sender<Message>) -> Choice {
+    let mut choice = Choice::default()
+        .with_size(LANG_CHOICE_WIDTH, LANG_CHOICE_HEIGHT)
+        .with_pos(LANG_CHOICE_XPOS, LANG_CHOICE_YPOS);
+    choice.set_text_size(LANG_CHOICE_TXT_SIZE);
+    choice.set_color(C_DDLC_WHITE_ACT);
+    choice.set_text_color(C_BLACK);
+    choice.set_selection_color(C_DDLC_PINK_DARK);
+    let current_language = get_language();
+    for (i, language) in Language::ALL.into_iter().enumerate() {
+        choice.add_choice(language.get_name());
+        if language == current_language {
+            choice.set_value(i as i32);
+        }
+    }
+    choice.set_callback(
+        move |c| {
+            if let Some(&language) = usize::try_from(c.value()).ok().and_then(|i| Language::ALL.get(i)) {
+                sender.send(Message::LanguageSelected(language));
+            }
+        }
     );
 
-    pack.end();
+    return choice;
+}
 
-    return pack;
+/// Builds a text display for the release notes
+fn _build_release_notes_txt(buf: TextBuffer) -> TextDisplay {
+    let mut txt = TextDisplay::default()
+        .with_size(RELEASE_NOTES_TXT_WIDTH, RELEASE_NOTES_TXT_HEIGHT)
+        .with_pos(RELEASE_NOTES_TXT_XPOS, RELEASE_NOTES_TXT_YPOS);
+    txt.set_text_size(RELEASE_NOTES_TXT_SIZE);
+    txt.wrap_mode(WrapMode::AtBounds, 0);
+    txt.set_frame(FrameType::EngravedFrame);
+    txt.set_color(C_DDLC_WHITE_ACT);
+    txt.set_text_color(C_BLACK);
+    txt.set_selection_color(C_DDLC_PINK_DARK);
+    txt.set_buffer(buf);
+
+    return txt;
 }
 
-/// Builds a pack of buttons for the welcome window
-fn _build_welcome_win_pack(sender: Sender<Message>) -> Pack {
-    let outer_pack = _build_welcome_win_outer_pack();
-    outer_pack.begin();
+/// Builds the welcome windows
+pub fn build_welcome_win(sender: Sender<Message>, is_dark_theme: bool, release_notes_buf: TextBuffer) -> DoubleWindow {
+    let mut welcome_win = build_inner_win();
+    welcome_win.show();
+    welcome_win.begin();
 
-    let inner_pack = _build_welcome_win_inner_pack();
-    inner_pack.begin();
+    _build_top_frame(get_strings().welcome_top_frame_label);
+    let mut mid_frame = _build_mid_frame(get_strings().welcome_mid_frame_label);
+    mid_frame.set_size(MID_FRAME_WIDTH, WELCOME_MID_FRAME_HEIGHT);
+    _build_release_notes_txt(release_notes_buf);
 
-    build_button(BUT_ABORT_LABEL, sender, Message::Abort);
-    build_volume_but(sender);
+    let pack = _build_welcome_win_pack(sender);
 
-    inner_pack.end();
+    // Offline install, goes above the continue button
+    let mut but_from_file = build_button(get_strings().but_from_file_label, sender, Message::InstallFromFile);
+    but_from_file.set_pos(pack.x() + pack.width() - BUT_WIDTH, pack.y() - BUT_HEIGHT - BUT_SPACING);
 
-    build_button(BUT_CONTINUE_LABEL, sender, Message::NextPage);
+    // Installer info, goes next to the offline install button
+    let mut but_about = build_button(get_strings().but_about_label, sender, Message::About);
+    but_about.set_pos(but_from_file.x() - BUT_WIDTH - BUT_SPACING, but_from_file.y());
 
-    outer_pack.end();
+    // Check an existing install, goes above the offline install button
+    let mut but_verify = build_button(get_strings().but_verify_label, sender, Message::Verify);
+    but_verify.set_pos(but_from_file.x(), but_from_file.y() - BUT_HEIGHT - BUT_SPACING);
 
-    return outer_pack;
-}
+    // Spritepacks onto an existing install, goes next to the info button
+    let mut but_spr_only = build_button(get_strings().but_spr_only_label, sender, Message::SpritepacksOnly);
+    but_spr_only.set_pos(but_about.x() - BUT_WIDTH - BUT_SPACING, but_about.y());
 
-/// Builds the welcome windows
-pub fn build_welcome_win(sender: Sender<Message>) -> DoubleWindow {
-    let mut welcome_win = build_inner_win();
-    welcome_win.show();
-    welcome_win.begin();
+    // Install into a copy of DDLC, goes above the info button
+    let mut but_copy_ddlc = build_button(get_strings().but_copy_ddlc_label, sender, Message::CopyDdlc);
+    but_copy_ddlc.set_pos(but_about.x(), but_verify.y());
+    but_copy_ddlc.set_tooltip(get_strings().but_copy_ddlc_tooltip);
 
-    _build_top_frame(WELCOME_TOP_FRAME_LABEL);
-    _build_mid_frame(WELCOME_MID_FRAME_LABEL);
+    // Theme toggle, goes above the abort button
+    let mut but_dark_theme = _build_check_button(
+        BUT_DARK_THEME_CHECK_WIDTH,
+        BUT_DARK_THEME_CHECK_HEIGHT,
+        get_strings().but_dark_theme_check_label,
+        sender,
+        Message::DarkThemeCheck,
+        is_dark_theme
+    );
+    but_dark_theme.set_pos(pack.x(), pack.y() - BUT_DARK_THEME_CHECK_HEIGHT - BUT_SPACING);
 
-    _build_welcome_win_pack(sender);
+    _build_language_choice(sender);
 
     welcome_win.end();
 
@@ -439,7 +567,8 @@ fn _build_4but_right_inner_pack() -> Pack {
 /// Builds a pack of 4 buttons
 fn _build_4but_pack(
     sender: Sender<Message>,
-    but3_data: (&str, Message)
+    but3_data: (&str, Message),
+    but4_data: (&str, Message)
 ) -> Pack {
     let mut outer_pack = _build_4but_outer_pack();
     outer_pack.begin();
@@ -447,7 +576,7 @@ fn _build_4but_pack(
     let left_inner_pack = _build_4but_left_inner_pack();
     left_inner_pack.begin();
 
-    build_button(BUT_ABORT_LABEL, sender, Message::Abort);
+    build_button(get_strings().but_abort_label, sender, Message::Abort);
     build_volume_but(sender);
 
     left_inner_pack.end();
@@ -455,8 +584,8 @@ fn _build_4but_pack(
     let right_inner_pack = _build_4but_right_inner_pack();
     right_inner_pack.begin();
 
-    build_button(BUT_BACK_LABEL, sender, Message::PrevPage);
     build_button(but3_data.0, sender, but3_data.1);
+    build_button(but4_data.0, sender, but4_data.1);
 
     right_inner_pack.end();
 
@@ -473,7 +602,8 @@ fn _build_4but_pack(
 fn _build_abort_back_contn_pack(sender: Sender<Message>) {
     _build_4but_pack(
         sender,
-        (BUT_CONTINUE_LABEL, Message::NextPage)
+        (get_strings().but_back_label, Message::PrevPage),
+        (get_strings().but_continue_label, Message::NextPage)
     );
 }
 
@@ -482,7 +612,8 @@ fn _build_abort_back_contn_pack(sender: Sender<Message>) {
 fn _build_abort_back_inst_pack(sender: Sender<Message>) {
     _build_4but_pack(
         sender,
-        (BUT_INSTALL_LABEL, Message::Install)
+        (get_strings().but_back_label, Message::PrevPage),
+        (get_strings().but_install_label, Message::Install)
     );
 }
 
@@ -570,6 +701,8 @@ fn build_license_txt(buf: TextBuffer) -> TextDisplay {
         .with_size(TXT_DISP_WIDTH, TXT_DISP_HEIGHT)
         .with_pos(TXT_DISP_XPOS, TXT_DISP_YPOS);
         txt_disp.wrap_mode(WrapMode::AtBounds, 0);
+        txt_disp.set_color(C_DDLC_WHITE_ACT);
+        txt_disp.set_text_color(C_BLACK);
         txt_disp.set_selection_color(C_DDLC_PINK_DARK);
         txt_disp.set_scrollbar_size(-1);
         txt_disp.set_buffer(buf);
@@ -583,7 +716,7 @@ pub fn build_license_win(sender: Sender<Message>) -> DoubleWindow {
     license_win.begin();
 
 
-    _build_top_frame(LICENSE_FRAME_LABEL);
+    _build_top_frame(get_strings().license_frame_label);
 
     let mut buf = TextBuffer::default();
     buf.set_text(static_data::APP_LICENSE);
@@ -617,7 +750,7 @@ pub fn build_select_dir_win(sender: Sender<Message>, txt_buf: TextBuffer) -> Dou
     select_dir_win.begin();
 
 
-    _build_top_frame(SELECT_DIR_FRAME_LABEL);
+    _build_top_frame(get_strings().select_dir_frame_label);
 
     let mut txt = TextDisplay::default()
         .with_size(SEL_DIR_TXT_WIDTH, SEL_DIR_TXT_HEIGHT)
@@ -625,11 +758,13 @@ pub fn build_select_dir_win(sender: Sender<Message>, txt_buf: TextBuffer) -> Dou
     txt.set_text_size(SEL_DIR_TXT_SIZE);
     txt.wrap_mode(WrapMode::None, 0);
     txt.set_frame(FrameType::EngravedFrame);
+    txt.set_color(C_DDLC_WHITE_ACT);
+    txt.set_text_color(C_BLACK);
     txt.set_selection_color(C_DDLC_PINK_DARK);
     txt.set_scrollbar_size(-1);
     txt.set_buffer(txt_buf);
 
-    let mut but = build_button(BUT_SELECT_DIR_LABEL, sender, Message::SelectDir);
+    let mut but = build_button(get_strings().but_select_dir_label, sender, Message::SelectDir);
     but.set_pos(INNER_WIN_CONTENT_XPADDING+SEL_DIR_TXT_WIDTH-BUT_WIDTH, SEL_DIR_TXT_YPOS+SEL_DIR_TXT_HEIGHT+BUT_SPACING/2);
 
     _build_abort_back_contn_pack(sender);
@@ -641,16 +776,64 @@ pub fn build_select_dir_win(sender: Sender<Message>, txt_buf: TextBuffer) -> Dou
 }
 
 
+/// Builds a dropdown to select the version to install
+/// initially it only has the latest version
+pub fn build_version_choice(sender: Sender<Message>) -> Choice {
+    let mut choice = Choice::default()
+        .with_size(VER_CHOICE_WIDTH, VER_CHOICE_HEIGHT)
+        .with_pos(VER_CHOICE_XPOS, VER_CHOICE_YPOS);
+    choice.set_text_font(BUT_FONT);
+    choice.set_text_size(VER_CHOICE_TXT_SIZE);
+    choice.set_color(C_DDLC_WHITE_ACT);
+    choice.set_text_color(C_BLACK);
+    choice.set_selection_color(C_DDLC_PINK_DARK);
+    choice.add_choice(get_strings().ver_choice_latest_label);
+    choice.set_value(0);
+    choice.emit(sender, Message::VersionSelected);
+
+    return choice;
+}
+
+/// Builds the select version window
+pub fn build_select_version_win(sender: Sender<Message>, choice: &Choice) -> DoubleWindow {
+    let mut select_ver_win = build_inner_win();
+    select_ver_win.begin();
+
+
+    _build_top_frame(get_strings().select_version_frame_label);
+
+    select_ver_win.add(choice);
+
+    _build_abort_back_contn_pack(sender);
+
+
+    select_ver_win.end();
+
+    return select_ver_win;
+}
+
+
 /// Builds the options window with various settings for installer
-pub fn build_options_win(sender: Sender<Message>, is_dlx_version: bool, install_spr: bool) -> DoubleWindow {
-    let options_win = build_inner_win();
+pub fn build_options_win(
+    sender: Sender<Message>,
+    is_dlx_version: bool,
+    install_spr: bool,
+    backup: bool,
+    shortcut: bool,
+    keep_archives: bool,
+    dry_run: bool,
+    prerelease: bool,
+    speed_limit: Option<f64>,
+    download_size_frame: &Frame
+) -> DoubleWindow {
+    let mut options_win = build_inner_win();
     options_win.begin();
 
 
-    _build_top_frame(OPTIONS_FRAME_LABEL);
+    _build_top_frame(get_strings().options_frame_label);
 
 
-    const TOTAL_BUTS: i32 = 2;
+    const TOTAL_BUTS: i32 = 7;
     const XPOS: i32 = INNER_WIN_CONTENT_XPADDING;
     const YPOS: i32 = INNER_WIN_HEIGHT/2 - TOTAL_BUTS*BUT_HEIGHT/2 - (TOTAL_BUTS-1)*BUT_SPACING/2;
     const YPOS_INC: i32 = BUT_HEIGHT + BUT_SPACING;
@@ -658,24 +841,118 @@ pub fn build_options_win(sender: Sender<Message>, is_dlx_version: bool, install_
     let mut but_inst_dlx = _build_check_button(
         BUT_DLX_VER_CHECK_WIDTH,
         BUT_DLX_VER_CHECK_HEIGHT,
-        BUT_DLX_VER_CHECK_LABEL,
+        get_strings().but_dlx_ver_check_label,
         sender,
         Message::DlxVersionCheck,
         is_dlx_version
     );
     but_inst_dlx.set_pos(XPOS, YPOS);
+    let mut but_license = build_button(get_strings().but_license_label, sender, Message::ViewLicense);
+    but_license.set_pos(INNER_WIN_WIDTH - INNER_WIN_CONTENT_XPADDING - BUT_WIDTH, YPOS);
+    but_license.set_tooltip(get_strings().but_license_tooltip);
     let mut but_inst_spr = _build_check_button(
         BUT_INSTALL_SPR_CHECK_WIDTH,
         BUT_INSTALL_SPR_CHECK_HEIGHT,
-        BUT_INSTALL_SPR_CHECK_LABEL,
+        get_strings().but_install_spr_check_label,
         sender,
         Message::InstallSprCheck,
         install_spr
     );
     but_inst_spr.set_pos(XPOS, YPOS+YPOS_INC);
+    let mut but_spr_bundles = build_button(get_strings().but_spr_bundles_label, sender, Message::SprBundles);
+    but_spr_bundles.set_pos(XPOS + BUT_INSTALL_SPR_CHECK_WIDTH + BUT_SPACING, YPOS+YPOS_INC);
+    let mut but_backup = _build_check_button(
+        BUT_BACKUP_CHECK_WIDTH,
+        BUT_BACKUP_CHECK_HEIGHT,
+        get_strings().but_backup_check_label,
+        sender,
+        Message::BackupCheck,
+        backup
+    );
+    but_backup.set_pos(XPOS, YPOS+2*YPOS_INC);
+    let mut but_shortcut = _build_check_button(
+        BUT_SHORTCUT_CHECK_WIDTH,
+        BUT_SHORTCUT_CHECK_HEIGHT,
+        get_strings().but_shortcut_check_label,
+        sender,
+        Message::ShortcutCheck,
+        shortcut
+    );
+    but_shortcut.set_pos(XPOS, YPOS+3*YPOS_INC);
+    if !platform::get_capabilities().shortcuts {
+        but_shortcut.set_checked(false);
+        but_shortcut.deactivate();
+        but_shortcut.set_tooltip(get_strings().unsupported_feature_tooltip);
+    }
+    let mut but_keep_archives = _build_check_button(
+        BUT_KEEP_ARCHIVES_CHECK_WIDTH,
+        BUT_KEEP_ARCHIVES_CHECK_HEIGHT,
+        get_strings().but_keep_archives_check_label,
+        sender,
+        Message::KeepArchivesCheck,
+        keep_archives
+    );
+    but_keep_archives.set_pos(XPOS + BUT_SHORTCUT_CHECK_WIDTH + BUT_SPACING, YPOS+3*YPOS_INC);
+    let mut but_dry_run = _build_check_button(
+        BUT_DRY_RUN_CHECK_WIDTH,
+        BUT_DRY_RUN_CHECK_HEIGHT,
+        get_strings().but_dry_run_check_label,
+        sender,
+        Message::DryRunCheck,
+        dry_run
+    );
+    but_dry_run.set_pos(XPOS, YPOS+4*YPOS_INC);
+    let mut but_prerelease = _build_check_button(
+        BUT_PRERELEASE_CHECK_WIDTH,
+        BUT_PRERELEASE_CHECK_HEIGHT,
+        get_strings().but_prerelease_check_label,
+        sender,
+        Message::PrereleaseCheck,
+        prerelease
+    );
+    but_prerelease.set_pos(XPOS, YPOS+5*YPOS_INC);
+
+    let warn_xpos = XPOS + BUT_PRERELEASE_CHECK_WIDTH + BUT_SPACING;
+    let mut warn_frame = Frame::default()
+        .with_size(INNER_WIN_WIDTH - warn_xpos - INNER_WIN_CONTENT_XPADDING, BUT_PRERELEASE_CHECK_HEIGHT)
+        .with_pos(warn_xpos, YPOS+5*YPOS_INC)
+        .with_align(Align::Left | Align::Inside)
+        .with_label(get_strings().prerelease_warn_label);
+    warn_frame.set_label_color(C_DDLC_PINK_DARK);
+    warn_frame.set_label_size(WARN_FRAME_LABEL_SIZE);
+
+    let mut but_proxy = build_button(get_strings().but_proxy_label, sender, Message::ProxySettings);
+    but_proxy.set_pos(XPOS, YPOS+6*YPOS_INC);
+
+    let mut speed_limit_input = FloatInput::default()
+        .with_size(SPEED_LIMIT_INPUT_WIDTH, SPEED_LIMIT_INPUT_HEIGHT)
+        .with_pos(XPOS + BUT_WIDTH + BUT_SPACING, YPOS+6*YPOS_INC)
+        .with_align(Align::Right)
+        .with_label(get_strings().speed_limit_input_label);
+    speed_limit_input.set_label_color(C_BLACK);
+    speed_limit_input.set_label_size(SPEED_LIMIT_LABEL_SIZE);
+    speed_limit_input.set_color(C_DDLC_WHITE_ACT);
+    speed_limit_input.set_text_color(C_BLACK);
+    speed_limit_input.set_text_size(SPEED_LIMIT_INPUT_TEXT_SIZE);
+    if let Some(limit) = speed_limit {
+        speed_limit_input.set_value(&limit.to_string());
+    }
+    speed_limit_input.set_trigger(CallbackTrigger::Changed);
+    speed_limit_input.set_callback(
+        move |input| {
+            // Anything we can't use means no limit
+            let limit = input.value().trim().parse::<f64>().ok()
+                .filter(|limit| limit.is_finite() && *limit > 0.0);
+            sender.send(Message::SpeedLimitChanged(limit));
+        }
+    );
 
+    // Goes under the options, the text is set by the app
+    download_size_frame.clone().set_pos(XPOS, YPOS+7*YPOS_INC);
+    options_win.add(download_size_frame);
 
-    _build_abort_back_inst_pack(sender);
+
+    _build_abort_back_contn_pack(sender);
 
 
     options_win.end();
@@ -684,38 +961,179 @@ pub fn build_options_win(sender: Sender<Message>, is_dlx_version: bool, install_
 }
 
 
-/// Builds a progress bar
+/// Builds the window with the summary of the chosen settings,
+/// the text is filled in when the window is shown
+pub fn build_summary_win(sender: Sender<Message>, summary_buf: TextBuffer) -> DoubleWindow {
+    let summary_win = build_inner_win();
+    summary_win.begin();
+
+
+    _build_top_frame(get_strings().summary_frame_label);
+
+    let mut txt = TextDisplay::default()
+        .with_size(TXT_DISP_WIDTH, SUMMARY_TXT_HEIGHT)
+        .with_pos(TXT_DISP_XPOS, TXT_DISP_YPOS);
+    txt.set_text_size(SUMMARY_TXT_SIZE);
+    txt.wrap_mode(WrapMode::AtBounds, 0);
+    txt.set_frame(FrameType::EngravedFrame);
+    txt.set_color(C_DDLC_WHITE_ACT);
+    txt.set_text_color(C_BLACK);
+    txt.set_selection_color(C_DDLC_PINK_DARK);
+    txt.set_buffer(summary_buf);
+
+    // Same as on the select dir page, but without going back
+    let mut but_change_dir = build_button(get_strings().but_change_dir_label, sender, Message::SelectDir);
+    but_change_dir.set_pos(SUMMARY_BUT_CHANGE_DIR_XPOS, SUMMARY_BUT_CHANGE_DIR_YPOS);
+    but_change_dir.set_tooltip(get_strings().but_change_dir_tooltip);
+
+    _build_abort_back_inst_pack(sender);
+
+
+    summary_win.end();
+
+    return summary_win;
+}
+
+
+/// Builds a progress bar, the label is drawn over the bar
+/// and is used to show the percentage
 pub fn build_progress_bar() -> Progress {
     let mut bar = Progress::default()
         .with_size(PB_WIDTH, PB_HEIGHT)
-        .with_pos(INNER_WIN_CONTENT_XPADDING, WIN_HEIGHT/2-PB_HEIGHT/2);
+        .with_pos(PB_XPOS, PB_YPOS)
+        .with_label(&format_progress_percentage(0.0));
     bar.set_minimum(0.0);
     bar.set_maximum(1.0);
+    bar.set_align(Align::Center | Align::Inside);
     bar.set_label_font(BUT_FONT);
+    bar.set_label_size(PB_LABEL_SIZE);
     bar.set_color(C_WHITE);
+    bar.set_label_color(C_BLACK);
     bar.set_selection_color(C_BRIGHT_GREEN);
 
     return bar;
 }
 
+/// Formats the progress bar value into a percentage string
+/// NOTE: this rounds down so the label doesn't jump back and forth
+pub fn format_progress_percentage(value: f64) -> String {
+    let percentage = (value.clamp(0.0, 1.0) * 100.0).floor() as u8;
+    return format!("{percentage}%");
+}
+
+/// Builds a frame for the progress window
+fn _build_progress_frame(width: i32, height: i32, xpos: i32, ypos: i32, label_size: i32) -> Frame {
+    let mut frame = Frame::default()
+        .with_size(width, height)
+        .with_pos(xpos, ypos);
+    // Needs a box, so the old text gets cleared on redraw
+    frame.set_frame(FrameType::FlatBox);
+    frame.set_color(C_DDLC_WHITE_IDLE);
+    frame.set_align(Align::Center | Align::Inside);
+    frame.set_label_color(C_DDLC_PINK_DARK);
+    frame.set_label_size(label_size);
+
+    return frame;
+}
+
+/// Builds a frame above the progress bar to show the current phase
+pub fn build_progress_phase_frame() -> Frame {
+    return _build_progress_frame(
+        PB_PHASE_WIDTH,
+        PB_PHASE_HEIGHT,
+        PB_XPOS,
+        PB_PHASE_YPOS,
+        PB_PHASE_LABEL_SIZE
+    );
+}
+
+/// Builds a frame above the phase to show the version being installed
+pub fn build_progress_version_frame() -> Frame {
+    return _build_progress_frame(
+        PB_VERSION_WIDTH,
+        PB_VERSION_HEIGHT,
+        PB_XPOS,
+        PB_VERSION_YPOS,
+        PB_VERSION_LABEL_SIZE
+    );
+}
+
+/// Builds a frame under the progress bar to show additional info
+pub fn build_progress_info_frame() -> Frame {
+    return _build_progress_frame(
+        PB_INFO_WIDTH,
+        PB_INFO_HEIGHT,
+        PB_XPOS,
+        PB_YPOS+PB_HEIGHT+BUT_SPACING,
+        PB_INFO_LABEL_SIZE
+    );
+}
+
+/// Builds the button to pause/resume the download
+/// NOTE: hidden until the download starts
+pub fn build_pause_button(sender: Sender<Message>) -> Button {
+    let mut but = build_button(get_strings().but_pause_label, sender, Message::TogglePause);
+    but.hide();
+    return but;
+}
+
+/// Builds a frame for the options window to show the total download size
+pub fn build_download_size_frame() -> Frame {
+    return _build_progress_frame(
+        DL_SIZE_FRAME_WIDTH,
+        DL_SIZE_FRAME_HEIGHT,
+        0,
+        0,
+        DL_SIZE_FRAME_LABEL_SIZE
+    );
+}
+
+/// Builds a frame for the done window to list the kept archives
+pub fn build_kept_archives_frame() -> Frame {
+    return _build_progress_frame(
+        KEPT_ARCHIVES_FRAME_WIDTH,
+        KEPT_ARCHIVES_FRAME_HEIGHT,
+        KEPT_ARCHIVES_FRAME_XPOS,
+        KEPT_ARCHIVES_FRAME_YPOS,
+        KEPT_ARCHIVES_FRAME_LABEL_SIZE
+    );
+}
+
 /// Builds the downloading/installing window
-pub fn build_propgress_win(sender: Sender<Message>, bar: &Progress) -> DoubleWindow {
+pub fn build_propgress_win(
+    sender: Sender<Message>,
+    bar: &Progress,
+    version_frame: &Frame,
+    phase_frame: &Frame,
+    info_frame: &Frame,
+    pause_but: &Button
+) -> DoubleWindow {
     let mut progress_win = build_inner_win();
     progress_win.begin();
 
 
-    _build_top_frame(PROGRESS_FRAME_LABEL);
+    _build_top_frame(get_strings().progress_frame_label);
 
     let mut pack = _build_4but_left_inner_pack();
     pack.set_pos(INNER_WIN_CONTENT_XPADDING, INNER_WIN_HEIGHT-BUT_HEIGHT-BUT_PACK_YPADDING);
     pack.begin();
 
-    build_button(BUT_ABORT_LABEL, sender, Message::Abort);
+    build_button(get_strings().but_abort_label, sender, Message::Abort);
     build_volume_but(sender);
 
     pack.end();
 
+    let mut but_cancel = build_button(get_strings().but_cancel_label, sender, Message::Cancel);
+    but_cancel.set_pos(INNER_WIN_WIDTH-BUT_WIDTH-INNER_WIN_CONTENT_XPADDING, INNER_WIN_HEIGHT-BUT_HEIGHT-BUT_PACK_YPADDING);
+
+    // Goes next to the cancel button, the app changes the label
+    pause_but.clone().set_pos(but_cancel.x()-BUT_WIDTH-BUT_SPACING, but_cancel.y());
+    progress_win.add(pause_but);
+
+    progress_win.add(version_frame);
+    progress_win.add(phase_frame);
     progress_win.add(bar);
+    progress_win.add(info_frame);
 
 
     progress_win.end();
@@ -723,6 +1141,60 @@ pub fn build_propgress_win(sender: Sender<Message>, bar: &Progress) -> DoubleWin
     return progress_win;
 }
 
+/// Puts the bar and the phase frame back onto the progress window
+/// after the compact window is closed
+pub fn attach_progress_widgets(progress_win: &mut DoubleWindow, bar: &Progress, phase_frame: &Frame) {
+    phase_frame.clone().set_pos(PB_XPOS, PB_PHASE_YPOS);
+    bar.clone().set_pos(PB_XPOS, PB_YPOS);
+    progress_win.add(phase_frame);
+    progress_win.add(bar);
+}
+
+/// Builds the compact window, just the title, the progress bar and the cancel button,
+/// it's shown instead of the main window for repeat users
+/// NOTE: this moves the bar and the phase frame from the progress window
+pub fn build_compact_progress_win(sender: Sender<Message>, bar: &Progress, phase_frame: &Frame) -> DoubleWindow {
+    let mut compact_win = Window::default()
+        .with_size(COMPACT_WIN_WIDTH, COMPACT_WIN_HEIGHT)
+        .with_label(&format_outer_win_title())
+        .center_screen();
+    compact_win.set_color(C_DDLC_PINK_IDLE);
+    // Same as the main window, the app decides what to do
+    compact_win.set_callback(
+        move |_| {
+            if get_last_event() == Event::Close {
+                sender.send(Message::WindowClose);
+            };
+        }
+    );
+    load_icon(&mut compact_win);
+
+    let mut inner_win = Window::default()
+        .with_size(INNER_COMPACT_WIN_WIDTH, INNER_COMPACT_WIN_HEIGHT)
+        .with_pos(WIN_PADDING, WIN_PADDING);
+    inner_win.set_color(C_DDLC_WHITE_IDLE);
+
+
+    _build_top_frame(get_strings().progress_frame_label);
+
+    phase_frame.clone().set_pos(PB_XPOS, COMPACT_PB_PHASE_YPOS);
+    bar.clone().set_pos(PB_XPOS, COMPACT_PB_YPOS);
+    inner_win.add(phase_frame);
+    inner_win.add(bar);
+
+    let mut but_cancel = build_button(get_strings().but_cancel_label, sender, Message::Cancel);
+    but_cancel.set_pos(
+        INNER_COMPACT_WIN_WIDTH-BUT_WIDTH-INNER_WIN_CONTENT_XPADDING,
+        INNER_COMPACT_WIN_HEIGHT-BUT_HEIGHT-BUT_PACK_YPADDING
+    );
+
+
+    inner_win.end();
+    compact_win.end();
+
+    return compact_win;
+}
+
 
 /// Formats a message to
 /// text:
@@ -745,7 +1217,7 @@ fn _build_msg_box_ok_but(msg_box_win: &DoubleWindow, ypadding: i32) -> Button {
     let mut but = _build_button_base(
         BUT_WIDTH,
         BUT_HEIGHT,
-        BUT_OK_LABEL,
+        get_strings().but_ok_label,
         _handle_button,
         _draw_button
     );
@@ -772,7 +1244,7 @@ pub fn build_alert_win(msg: &str) -> DoubleWindow {
     let mut alert_win = Window::default()
         .with_size(ALERT_WIN_WIDTH, ALERT_WIN_HEIGHT)
         .with_pos(win_x, win_y)
-        .with_label(ALERT_WIN_TITLE);
+        .with_label(get_strings().alert_win_title);
     alert_win.set_color(C_DDLC_PINK_IDLE);
 
     let mut inner_win = Window::default()
@@ -792,7 +1264,10 @@ pub fn build_alert_win(msg: &str) -> DoubleWindow {
             INNER_ALERT_WIN_HEIGHT - BUT_HEIGHT - 2*BUT_ALERT_WIN_PADDING
         )
         .with_pos(0, 0);
+    txt.set_color(C_DDLC_WHITE_ACT);
+    txt.set_text_color(C_BLACK);
     txt.set_buffer(buf);
+    txt.wrap_mode(WrapMode::AtBounds, 0);
 
 
     _build_msg_box_ok_but(&alert_win, BUT_ALERT_WIN_PADDING);
@@ -807,6 +1282,118 @@ pub fn build_alert_win(msg: &str) -> DoubleWindow {
     return alert_win;
 }
 
+/// Builds an alert window to show an installation error
+/// with the path to the install log and a button to open it,
+/// the cell is set to true if the user wants to open the log
+/// if there are error details, the user can copy them for a bug report
+pub fn build_error_win(msg: &str, log_path: &Path, details: Option<&str>, open_log: Rc<Cell<bool>>) -> DoubleWindow {
+    let (sw, sh) = screen_size();
+
+    let win_x = sw as i32/2 - ALERT_WIN_WIDTH/2;
+    let win_y = sh as i32/2 - ALERT_WIN_HEIGHT/2;
+
+    let mut error_win = Window::default()
+        .with_size(ALERT_WIN_WIDTH, ALERT_WIN_HEIGHT)
+        .with_pos(win_x, win_y)
+        .with_label(get_strings().alert_win_title);
+    error_win.set_color(C_DDLC_PINK_IDLE);
+
+    let mut inner_win = Window::default()
+        .with_size(INNER_ALERT_WIN_WIDTH, INNER_ALERT_WIN_HEIGHT)
+        .with_pos(WIN_PADDING, WIN_PADDING);
+    inner_win.set_color(C_DDLC_WHITE_IDLE);
+
+
+    let mut buf = TextBuffer::default();
+    buf.set_text(
+        &format!(
+            "{}\n{}",
+            __format_alert_msg(msg),
+            get_strings().install_log_info.replace("{path}", &log_path.display().to_string())
+        )
+    );
+
+    let mut txt = TextDisplay::default()
+        .with_size(
+            INNER_ALERT_WIN_WIDTH,
+            INNER_ALERT_WIN_HEIGHT - BUT_HEIGHT - 2*BUT_ALERT_WIN_PADDING
+        )
+        .with_pos(0, 0);
+    txt.set_color(C_DDLC_WHITE_ACT);
+    txt.set_text_color(C_BLACK);
+    txt.set_buffer(buf);
+    txt.wrap_mode(WrapMode::AtBounds, 0);
+
+
+    let but_ypos = INNER_ALERT_WIN_HEIGHT - BUT_HEIGHT - BUT_ALERT_WIN_PADDING;
+    // Make room for the copy button in the middle
+    let side_but_offset = match details {
+        Some(_) => BUT_WIDTH/2 + BUT_SPACING,
+        None => 0
+    };
+
+    let mut open_log_but = _build_button_base(
+        BUT_WIDTH,
+        BUT_HEIGHT,
+        get_strings().but_open_log_label,
+        _handle_button,
+        _draw_button
+    );
+    open_log_but.set_pos(INNER_ALERT_WIN_WIDTH/2 - BUT_WIDTH - BUT_SPACING - side_but_offset, but_ypos);
+    open_log_but.set_callback({
+        let mut win = error_win.clone();
+        let open_log = open_log.clone();
+        move |_| {
+            open_log.set(true);
+            win.hide();
+        }
+    });
+
+    let mut ok_but = _build_button_base(
+        BUT_WIDTH,
+        BUT_HEIGHT,
+        get_strings().but_ok_label,
+        _handle_button,
+        _draw_button
+    );
+    ok_but.set_pos(INNER_ALERT_WIN_WIDTH/2 + BUT_SPACING + side_but_offset, but_ypos);
+    ok_but.set_callback({
+        let mut win = error_win.clone();
+        move |_| {
+            open_log.set(false);
+            win.hide();
+        }
+    });
+
+    if let Some(details) = details {
+        let mut copy_but = _build_button_base(
+            BUT_WIDTH,
+            BUT_HEIGHT,
+            get_strings().but_copy_details_label,
+            _handle_button,
+            _draw_button
+        );
+        copy_but.set_pos(INNER_ALERT_WIN_WIDTH/2 - BUT_WIDTH/2, but_ypos);
+        copy_but.set_callback({
+            let details = details.to_owned();
+            move |b| {
+                copy_to_clipboard(&details);
+                b.set_label(get_strings().but_copied_label);
+                b.redraw();
+            }
+        });
+    }
+
+
+    inner_win.end();
+
+    error_win.end();
+    error_win.hide();
+    error_win.make_modal(true);
+
+    return error_win;
+}
+
 /// Builds a message box window to show some info to the user
 pub fn build_msg_win(msg: &str) -> DoubleWindow {
     let (sw, sh) = screen_size();
@@ -817,7 +1404,7 @@ pub fn build_msg_win(msg: &str) -> DoubleWindow {
     let mut alert_win = Window::default()
         .with_size(MSG_WIN_WIDTH, MSG_WIN_HEIGHT)
         .with_pos(win_x, win_y)
-        .with_label(MSG_WIN_TITLE);
+        .with_label(get_strings().msg_win_title);
     alert_win.set_color(C_DDLC_PINK_IDLE);
 
     let mut inner_win = Window::default()
@@ -847,38 +1434,734 @@ pub fn build_msg_win(msg: &str) -> DoubleWindow {
     return alert_win;
 }
 
+/// Builds a choice window to ask the user a yes/no question
+/// the answer is written into the given cell
+pub fn build_choice_win(msg: &str, yes_label: &str, no_label: &str, choice: Rc<Cell<bool>>) -> DoubleWindow {
+    let (sw, sh) = screen_size();
 
-fn _build_exit_button(sender: Sender<Message>) -> Button {
-    let mut but = build_button(BUT_EXIT_LABEL, sender, Message::Close);
-    but.set_pos(INNER_WIN_WIDTH-BUT_WIDTH-INNER_WIN_CONTENT_XPADDING, INNER_WIN_HEIGHT-BUT_HEIGHT-BUT_PACK_YPADDING);
+    let win_x = sw as i32/2 - CHOICE_WIN_WIDTH/2;
+    let win_y = sh as i32/2 - CHOICE_WIN_HEIGHT/2;
 
-    return but;
-}
+    let mut choice_win = Window::default()
+        .with_size(CHOICE_WIN_WIDTH, CHOICE_WIN_HEIGHT)
+        .with_pos(win_x, win_y)
+        .with_label(get_strings().choice_win_title);
+    choice_win.set_color(C_DDLC_PINK_IDLE);
 
-/// Builds the abort windows
-pub fn build_abort_win(sender: Sender<Message>) -> DoubleWindow {
-    let abort_win = build_inner_win();
-    abort_win.begin();
+    let mut inner_win = Window::default()
+        .with_size(INNER_CHOICE_WIN_WIDTH, INNER_CHOICE_WIN_HEIGHT)
+        .with_pos(WIN_PADDING, WIN_PADDING);
+    inner_win.set_color(C_DDLC_WHITE_IDLE);
 
-    _build_top_frame(ABORT_TOP_FRAME_LABEL);
-    _build_mid_frame(ABORT_MID_FRAME_LABEL);
 
-    _build_exit_button(sender);
+    let mut frame = Frame::default()
+        .with_size(INNER_CHOICE_WIN_WIDTH, INNER_CHOICE_WIN_HEIGHT - BUT_HEIGHT - 2*BUT_CHOICE_WIN_PADDING)
+        .with_pos(0, 0)
+        .with_align(Align::Center | Align::Inside)
+        .with_label(msg);
+    frame.set_label_color(C_DDLC_PINK_DARK);
+    frame.set_label_size(MSG_FRAME_LABEL_SIZE);
 
-    abort_win.end();
 
-    return abort_win;
-}
+    let but_ypos = INNER_CHOICE_WIN_HEIGHT - BUT_HEIGHT - BUT_CHOICE_WIN_PADDING;
 
-/// Builds the done windows
-pub fn build_done_win(sender: Sender<Message>) -> DoubleWindow {
-    let done_win = build_inner_win();
-    done_win.begin();
+    let mut yes_but = _build_button_base(
+        BUT_WIDTH,
+        BUT_HEIGHT,
+        yes_label,
+        _handle_button,
+        _draw_button
+    );
+    yes_but.set_pos(INNER_CHOICE_WIN_WIDTH/2 - BUT_WIDTH - BUT_SPACING, but_ypos);
+    yes_but.set_callback({
+        let mut win = choice_win.clone();
+        let choice = choice.clone();
+        move |_| {
+            choice.set(true);
+            win.hide();
+        }
+    });
 
-    _build_top_frame(DONE_TOP_FRAME_LABEL);
-    _build_mid_frame(DONE_MID_FRAME_LABEL);
+    let mut no_but = _build_button_base(
+        BUT_WIDTH,
+        BUT_HEIGHT,
+        no_label,
+        _handle_button,
+        _draw_button
+    );
+    no_but.set_pos(INNER_CHOICE_WIN_WIDTH/2 + BUT_SPACING, but_ypos);
+    no_but.set_callback({
+        let mut win = choice_win.clone();
+        move |_| {
+            choice.set(false);
+            win.hide();
+        }
+    });
 
-    _build_exit_button(sender);
+
+    inner_win.end();
+
+    choice_win.end();
+    choice_win.hide();
+    choice_win.make_modal(true);
+
+    return choice_win;
+}
+
+/// Builds a window to ask the user how to retry a failed installation,
+/// the picked mode is written into the given cell, None if the user wants to exit
+pub fn build_retry_win(msg: &str, choice: Rc<Cell<Option<RetryMode>>>) -> DoubleWindow {
+    let (sw, sh) = screen_size();
+
+    let win_x = sw as i32/2 - CHOICE_WIN_WIDTH/2;
+    let win_y = sh as i32/2 - CHOICE_WIN_HEIGHT/2;
+
+    let mut retry_win = Window::default()
+        .with_size(CHOICE_WIN_WIDTH, CHOICE_WIN_HEIGHT)
+        .with_pos(win_x, win_y)
+        .with_label(get_strings().choice_win_title);
+    retry_win.set_color(C_DDLC_PINK_IDLE);
+
+    let mut inner_win = Window::default()
+        .with_size(INNER_CHOICE_WIN_WIDTH, INNER_CHOICE_WIN_HEIGHT)
+        .with_pos(WIN_PADDING, WIN_PADDING);
+    inner_win.set_color(C_DDLC_WHITE_IDLE);
+
+
+    let mut frame = Frame::default()
+        .with_size(INNER_CHOICE_WIN_WIDTH, INNER_CHOICE_WIN_HEIGHT - BUT_HEIGHT - 2*BUT_CHOICE_WIN_PADDING)
+        .with_pos(0, 0)
+        .with_align(Align::Center | Align::Inside)
+        .with_label(msg);
+    frame.set_label_color(C_DDLC_PINK_DARK);
+    frame.set_label_size(MSG_FRAME_LABEL_SIZE);
+
+
+    let but_ypos = INNER_CHOICE_WIN_HEIGHT - BUT_HEIGHT - BUT_CHOICE_WIN_PADDING;
+    let buts = [
+        (get_strings().but_resume_label, get_strings().but_resume_tooltip, Some(RetryMode::Resume)),
+        (get_strings().but_start_over_label, get_strings().but_start_over_tooltip, Some(RetryMode::StartOver)),
+        (get_strings().but_exit_label, "", None)
+    ];
+    let mut but_xpos = INNER_CHOICE_WIN_WIDTH/2 - 3*BUT_WIDTH/2 - BUT_SPACING;
+    for (label, tooltip, mode) in buts {
+        let mut but = _build_button_base(
+            BUT_WIDTH,
+            BUT_HEIGHT,
+            label,
+            _handle_button,
+            _draw_button
+        );
+        but.set_pos(but_xpos, but_ypos);
+        if !tooltip.is_empty() {
+            but.set_tooltip(tooltip);
+        }
+        but.set_callback({
+            let mut win = retry_win.clone();
+            let choice = choice.clone();
+            move |_| {
+                choice.set(mode);
+                win.hide();
+            }
+        });
+        but_xpos += BUT_WIDTH + BUT_SPACING;
+    }
+
+
+    inner_win.end();
+
+    retry_win.end();
+    retry_win.hide();
+    retry_win.make_modal(true);
+
+    return retry_win;
+}
+
+
+/// Builds a window with proxy settings
+/// on Ok the entered url is written into the given RefCell,
+/// on Cancel/close it's left as None
+pub fn build_proxy_win(current_url: &str, result: Rc<RefCell<Option<String>>>) -> DoubleWindow {
+    let (sw, sh) = screen_size();
+
+    let win_x = sw as i32/2 - PROXY_WIN_WIDTH/2;
+    let win_y = sh as i32/2 - PROXY_WIN_HEIGHT/2;
+
+    let mut proxy_win = Window::default()
+        .with_size(PROXY_WIN_WIDTH, PROXY_WIN_HEIGHT)
+        .with_pos(win_x, win_y)
+        .with_label(get_strings().proxy_win_title);
+    proxy_win.set_color(C_DDLC_PINK_IDLE);
+
+    let mut inner_win = Window::default()
+        .with_size(INNER_PROXY_WIN_WIDTH, INNER_PROXY_WIN_HEIGHT)
+        .with_pos(WIN_PADDING, WIN_PADDING);
+    inner_win.set_color(C_DDLC_WHITE_IDLE);
+
+
+    let but_ypos = INNER_PROXY_WIN_HEIGHT - BUT_HEIGHT - BUT_CHOICE_WIN_PADDING;
+    let input_ypos = but_ypos - PROXY_INPUT_HEIGHT - BUT_CHOICE_WIN_PADDING;
+
+    let mut frame = Frame::default()
+        .with_size(INNER_PROXY_WIN_WIDTH, input_ypos)
+        .with_pos(0, 0)
+        .with_align(Align::Center | Align::Inside)
+        .with_label(get_strings().proxy_frame_label);
+    frame.set_label_color(C_DDLC_PINK_DARK);
+    frame.set_label_size(WARN_FRAME_LABEL_SIZE);
+
+    let mut input = Input::default()
+        .with_size(INNER_PROXY_WIN_WIDTH - 2*INNER_WIN_CONTENT_XPADDING, PROXY_INPUT_HEIGHT)
+        .with_pos(INNER_WIN_CONTENT_XPADDING, input_ypos);
+    input.set_text_size(PROXY_INPUT_TEXT_SIZE);
+    input.set_color(C_DDLC_WHITE_ACT);
+    input.set_text_color(C_BLACK);
+    input.set_value(current_url);
+
+
+    let mut ok_but = _build_button_base(
+        BUT_WIDTH,
+        BUT_HEIGHT,
+        get_strings().but_ok_label,
+        _handle_button,
+        _draw_button
+    );
+    ok_but.set_pos(INNER_PROXY_WIN_WIDTH/2 - BUT_WIDTH - BUT_SPACING, but_ypos);
+    ok_but.set_callback({
+        let mut win = proxy_win.clone();
+        move |_| {
+            result.replace(Some(input.value().trim().to_owned()));
+            win.hide();
+        }
+    });
+
+    let mut cancel_but = _build_button_base(
+        BUT_WIDTH,
+        BUT_HEIGHT,
+        get_strings().but_cancel_label,
+        _handle_button,
+        _draw_button
+    );
+    cancel_but.set_pos(INNER_PROXY_WIN_WIDTH/2 + BUT_SPACING, but_ypos);
+    cancel_but.set_callback({
+        let mut win = proxy_win.clone();
+        move |_| {
+            win.hide();
+        }
+    });
+
+
+    inner_win.end();
+
+    proxy_win.end();
+    proxy_win.hide();
+    proxy_win.make_modal(true);
+
+    return proxy_win;
+}
+
+/// Builds the window to pick the spritepack bundles, one check button per bundle,
+/// the result is set to the picked names if the user presses OK
+pub fn build_spr_bundles_win(
+    bundles: &[SprBundleInfo],
+    selected: &[String],
+    subfolders: bool,
+    result: Rc<RefCell<Option<(Vec<String>, bool)>>>
+) -> DoubleWindow {
+    let (sw, sh) = screen_size();
+
+    // The bundles, then the subfolders option
+    let checks_height = (bundles.len() as i32 + 1) * SPR_BUNDLES_CHECK_HEIGHT;
+    let inner_height = SPR_BUNDLES_FRAME_HEIGHT + checks_height + BUT_HEIGHT + 2*BUT_CHOICE_WIN_PADDING;
+    let win_height = inner_height + 2*WIN_PADDING;
+    let win_x = sw as i32/2 - SPR_BUNDLES_WIN_WIDTH/2;
+    let win_y = sh as i32/2 - win_height/2;
+
+    let mut bundles_win = Window::default()
+        .with_size(SPR_BUNDLES_WIN_WIDTH, win_height)
+        .with_pos(win_x, win_y)
+        .with_label(get_strings().spr_bundles_win_title);
+    bundles_win.set_color(C_DDLC_PINK_IDLE);
+
+    let mut inner_win = Window::default()
+        .with_size(INNER_SPR_BUNDLES_WIN_WIDTH, inner_height)
+        .with_pos(WIN_PADDING, WIN_PADDING);
+    inner_win.set_color(C_DDLC_WHITE_IDLE);
+
+
+    let mut frame = Frame::default()
+        .with_size(INNER_SPR_BUNDLES_WIN_WIDTH, SPR_BUNDLES_FRAME_HEIGHT)
+        .with_pos(0, 0)
+        .with_align(Align::Center | Align::Inside)
+        .with_label(get_strings().spr_bundles_frame_label);
+    frame.set_label_color(C_DDLC_PINK_DARK);
+    frame.set_label_size(WARN_FRAME_LABEL_SIZE);
+
+    let mut checks = Vec::with_capacity(bundles.len());
+    for (i, bundle) in bundles.iter().enumerate() {
+        let label = match bundle.size {
+            Some(size) => format!("{} ({})", bundle.name, format_size(size as f64)),
+            None => bundle.name.clone()
+        };
+        let mut check = _build_check_button_base(
+            INNER_SPR_BUNDLES_WIN_WIDTH - 2*INNER_WIN_CONTENT_XPADDING,
+            SPR_BUNDLES_CHECK_HEIGHT,
+            &label,
+            selected.contains(&bundle.name)
+        );
+        check.set_pos(INNER_WIN_CONTENT_XPADDING, SPR_BUNDLES_FRAME_HEIGHT + i as i32 * SPR_BUNDLES_CHECK_HEIGHT);
+        checks.push((bundle.name.clone(), check));
+    }
+    let mut subfolders_check = _build_check_button_base(
+        INNER_SPR_BUNDLES_WIN_WIDTH - 2*INNER_WIN_CONTENT_XPADDING,
+        SPR_BUNDLES_CHECK_HEIGHT,
+        get_strings().spr_subfolders_check_label,
+        subfolders
+    );
+    subfolders_check.set_pos(
+        INNER_WIN_CONTENT_XPADDING,
+        SPR_BUNDLES_FRAME_HEIGHT + bundles.len() as i32 * SPR_BUNDLES_CHECK_HEIGHT
+    );
+
+
+    let but_ypos = inner_height - BUT_HEIGHT - BUT_CHOICE_WIN_PADDING;
+
+    let mut ok_but = _build_button_base(
+        BUT_WIDTH,
+        BUT_HEIGHT,
+        get_strings().but_ok_label,
+        _handle_button,
+        _draw_button
+    );
+    ok_but.set_pos(INNER_SPR_BUNDLES_WIN_WIDTH/2 - BUT_WIDTH - BUT_SPACING, but_ypos);
+    ok_but.set_callback({
+        let mut win = bundles_win.clone();
+        move |_| {
+            let picked = checks.iter()
+                .filter(|(_, check)| check.is_checked())
+                .map(|(name, _)| name.clone())
+                .collect();
+            result.replace(Some((picked, subfolders_check.is_checked())));
+            win.hide();
+        }
+    });
+
+    let mut cancel_but = _build_button_base(
+        BUT_WIDTH,
+        BUT_HEIGHT,
+        get_strings().but_cancel_label,
+        _handle_button,
+        _draw_button
+    );
+    cancel_but.set_pos(INNER_SPR_BUNDLES_WIN_WIDTH/2 + BUT_SPACING, but_ypos);
+    cancel_but.set_callback({
+        let mut win = bundles_win.clone();
+        move |_| {
+            win.hide();
+        }
+    });
+
+
+    inner_win.end();
+
+    bundles_win.end();
+    bundles_win.hide();
+    bundles_win.make_modal(true);
+
+    return bundles_win;
+}
+
+/// Builds the about window with the installer version, the repo link and the license
+/// NOTE: the latest tag is None if we haven't fetched it
+pub fn build_about_win(repo: &str, latest_tag: Option<&str>) -> DoubleWindow {
+    let (sw, sh) = screen_size();
+
+    let win_x = sw as i32/2 - ABOUT_WIN_WIDTH/2;
+    let win_y = sh as i32/2 - ABOUT_WIN_HEIGHT/2;
+
+    let mut about_win = Window::default()
+        .with_size(ABOUT_WIN_WIDTH, ABOUT_WIN_HEIGHT)
+        .with_pos(win_x, win_y)
+        .with_label(get_strings().about_win_title);
+    about_win.set_color(C_DDLC_PINK_IDLE);
+
+    let mut inner_win = Window::default()
+        .with_size(INNER_ABOUT_WIN_WIDTH, INNER_ABOUT_WIN_HEIGHT)
+        .with_pos(WIN_PADDING, WIN_PADDING);
+    inner_win.set_color(C_DDLC_WHITE_IDLE);
+
+
+    let info = [
+        get_strings().about_version_info.replace("{version}", crate::VERSION.unwrap_or(crate::DEF_VERSION)),
+        get_strings().about_repo_info.replace("{repo}", &repo),
+        get_strings().about_release_info.replace(
+            "{tag}",
+            latest_tag.unwrap_or(get_strings().about_release_unknown)
+        )
+    ].join("\n");
+    let mut info_frame = Frame::default()
+        .with_size(INNER_ABOUT_WIN_WIDTH, ABOUT_INFO_FRAME_HEIGHT)
+        .with_pos(0, 0)
+        .with_align(Align::Center | Align::Inside);
+    // The tag comes from GitHub, make sure it can't be read as a symbol
+    info_frame.set_label(&info.replace('@', "@@"));
+    info_frame.set_label_color(C_DDLC_PINK_DARK);
+    info_frame.set_label_size(ABOUT_TXT_SIZE);
+
+    let url = format!("https://github.com/{repo}");
+    let mut link = Button::default()
+        .with_size(INNER_ABOUT_WIN_WIDTH, ABOUT_LINK_HEIGHT)
+        .with_pos(0, ABOUT_INFO_FRAME_HEIGHT)
+        .with_label(&url);
+    link.set_frame(FrameType::NoBox);
+    link.set_down_frame(FrameType::NoBox);
+    link.set_label_color(C_DDLC_PINK_ACT);
+    link.set_label_size(ABOUT_TXT_SIZE);
+    link.set_tooltip(&url);
+    link.set_callback(
+        move |_| {
+            if let Err(e) = open_url(&url) {
+                eprintln!("Failed to open {url}: {e}");
+            }
+        }
+    );
+
+
+    let but_ypos = INNER_ABOUT_WIN_HEIGHT - BUT_HEIGHT - BUT_ALERT_WIN_PADDING;
+    let txt_ypos = ABOUT_INFO_FRAME_HEIGHT + ABOUT_LINK_HEIGHT + BUT_SPACING;
+
+    let mut buf = TextBuffer::default();
+    buf.set_text(static_data::APP_LICENSE);
+
+    let mut txt = TextDisplay::default()
+        .with_size(
+            INNER_ABOUT_WIN_WIDTH - 2*INNER_WIN_CONTENT_XPADDING,
+            but_ypos - txt_ypos - BUT_ALERT_WIN_PADDING
+        )
+        .with_pos(INNER_WIN_CONTENT_XPADDING, txt_ypos);
+    txt.set_frame(FrameType::EngravedFrame);
+    txt.set_color(C_DDLC_WHITE_ACT);
+    txt.set_text_color(C_BLACK);
+    txt.set_text_size(ABOUT_TXT_SIZE);
+    txt.set_buffer(buf);
+    txt.wrap_mode(WrapMode::AtBounds, 0);
+
+
+    let mut ok_but = _build_button_base(
+        BUT_WIDTH,
+        BUT_HEIGHT,
+        get_strings().but_ok_label,
+        _handle_button,
+        _draw_button
+    );
+    ok_but.set_pos(INNER_ABOUT_WIN_WIDTH/2 - BUT_WIDTH/2, but_ypos);
+    ok_but.set_callback({
+        let mut win = about_win.clone();
+        move |_| win.hide()
+    });
+
+
+    inner_win.end();
+
+    about_win.end();
+    about_win.hide();
+    about_win.make_modal(true);
+
+    return about_win;
+}
+
+/// Builds the window with the license, if the full text wasn't compiled in,
+/// there's a notice above the link to it
+pub fn build_license_viewer_win() -> DoubleWindow {
+    let (sw, sh) = screen_size();
+
+    let win_x = sw as i32/2 - LICENSE_VIEWER_WIN_WIDTH/2;
+    let win_y = sh as i32/2 - LICENSE_VIEWER_WIN_HEIGHT/2;
+
+    let mut license_win = Window::default()
+        .with_size(LICENSE_VIEWER_WIN_WIDTH, LICENSE_VIEWER_WIN_HEIGHT)
+        .with_pos(win_x, win_y)
+        .with_label(get_strings().license_win_title);
+    license_win.set_color(C_DDLC_PINK_IDLE);
+
+    let mut inner_win = Window::default()
+        .with_size(INNER_LICENSE_VIEWER_WIN_WIDTH, INNER_LICENSE_VIEWER_WIN_HEIGHT)
+        .with_pos(WIN_PADDING, WIN_PADDING);
+    inner_win.set_color(C_DDLC_WHITE_IDLE);
+
+
+    let mut txt_ypos = BUT_ALERT_WIN_PADDING;
+    if !static_data::LICENSE_INCLUDED {
+        let mut notice_frame = Frame::default()
+            .with_size(INNER_LICENSE_VIEWER_WIN_WIDTH - 2*INNER_WIN_CONTENT_XPADDING, LICENSE_VIEWER_NOTICE_HEIGHT)
+            .with_pos(INNER_WIN_CONTENT_XPADDING, 0)
+            .with_align(Align::Center | Align::Inside | Align::Wrap)
+            .with_label(get_strings().license_not_included_info);
+        notice_frame.set_label_color(C_DDLC_PINK_DARK);
+        notice_frame.set_label_size(WARN_FRAME_LABEL_SIZE);
+        txt_ypos = LICENSE_VIEWER_NOTICE_HEIGHT;
+    }
+
+    let but_ypos = INNER_LICENSE_VIEWER_WIN_HEIGHT - BUT_HEIGHT - BUT_ALERT_WIN_PADDING;
+
+    let mut buf = TextBuffer::default();
+    buf.set_text(static_data::APP_LICENSE);
+
+    let mut txt = TextDisplay::default()
+        .with_size(
+            INNER_LICENSE_VIEWER_WIN_WIDTH - 2*INNER_WIN_CONTENT_XPADDING,
+            but_ypos - txt_ypos - BUT_ALERT_WIN_PADDING
+        )
+        .with_pos(INNER_WIN_CONTENT_XPADDING, txt_ypos);
+    txt.set_frame(FrameType::EngravedFrame);
+    txt.set_color(C_DDLC_WHITE_ACT);
+    txt.set_text_color(C_BLACK);
+    txt.set_text_size(ABOUT_TXT_SIZE);
+    txt.set_buffer(buf);
+    txt.wrap_mode(WrapMode::AtBounds, 0);
+
+
+    let mut ok_but = _build_button_base(
+        BUT_WIDTH,
+        BUT_HEIGHT,
+        get_strings().but_ok_label,
+        _handle_button,
+        _draw_button
+    );
+    ok_but.set_pos(INNER_LICENSE_VIEWER_WIN_WIDTH/2 - BUT_WIDTH/2, but_ypos);
+    ok_but.set_callback({
+        let mut win = license_win.clone();
+        move |_| win.hide()
+    });
+
+
+    inner_win.end();
+
+    license_win.end();
+    license_win.hide();
+    license_win.make_modal(true);
+
+    return license_win;
+}
+
+/// Builds the window with the dry run report,
+/// the cell is set to true if the user wants to proceed with the install
+pub fn build_dry_run_win(report: &DryRunReport, proceed: Rc<Cell<bool>>) -> DoubleWindow {
+    let summary = get_strings().dry_run_summary_info
+        .replace("{count}", &report.paths.len().to_string())
+        .replace("{size}", &format_size(report.total_size as f64));
+    let paths: Vec<String> = report.paths.iter().map(|path| path.display().to_string()).collect();
+
+    return _build_file_list_win(
+        get_strings().dry_run_win_title,
+        &summary,
+        &paths,
+        get_strings().but_proceed_label,
+        get_strings().but_cancel_label,
+        proceed
+    );
+}
+
+/// Builds the window with the missing and damaged files of an install,
+/// the cell is set to true if the user wants to repair them
+pub fn build_verify_win(report: &VerifyReport, repair: Rc<Cell<bool>>) -> DoubleWindow {
+    let summary = get_strings().verify_summary_info
+        .replace("{count}", &(report.missing.len() + report.damaged.len()).to_string())
+        .replace("{total}", &report.checked.to_string());
+    let mut paths: Vec<String> = report.missing.iter()
+        .map(|name| get_strings().verify_missing_info.replace("{name}", name))
+        .collect();
+    paths.extend(
+        report.damaged.iter().map(|name| get_strings().verify_damaged_info.replace("{name}", name))
+    );
+
+    return _build_file_list_win(
+        get_strings().verify_win_title,
+        &summary,
+        &paths,
+        get_strings().but_repair_label,
+        get_strings().but_cancel_label,
+        repair
+    );
+}
+
+/// Builds the window with the files the previous version left behind,
+/// the cell is set to true if the user wants to remove them
+pub fn build_stale_files_win(paths: &[String], remove: Rc<Cell<bool>>) -> DoubleWindow {
+    let summary = get_strings().stale_files_summary_info.replace("{count}", &paths.len().to_string());
+
+    return _build_file_list_win(
+        get_strings().stale_files_win_title,
+        &summary,
+        paths,
+        get_strings().but_remove_label,
+        get_strings().but_keep_label,
+        remove
+    );
+}
+
+/// Builds a modal window with a list of files and proceed/cancel buttons,
+/// the cell is set to true if the user wants to proceed
+fn _build_file_list_win(
+    title: &str,
+    summary: &str,
+    paths: &[String],
+    proceed_label: &str,
+    cancel_label: &str,
+    proceed: Rc<Cell<bool>>
+) -> DoubleWindow {
+    let (sw, sh) = screen_size();
+
+    let win_x = sw as i32/2 - DRY_RUN_WIN_WIDTH/2;
+    let win_y = sh as i32/2 - DRY_RUN_WIN_HEIGHT/2;
+
+    let mut dry_run_win = Window::default()
+        .with_size(DRY_RUN_WIN_WIDTH, DRY_RUN_WIN_HEIGHT)
+        .with_pos(win_x, win_y)
+        .with_label(title);
+    dry_run_win.set_color(C_DDLC_PINK_IDLE);
+
+    let mut inner_win = Window::default()
+        .with_size(INNER_DRY_RUN_WIN_WIDTH, INNER_DRY_RUN_WIN_HEIGHT)
+        .with_pos(WIN_PADDING, WIN_PADDING);
+    inner_win.set_color(C_DDLC_WHITE_IDLE);
+
+
+    let mut info_frame = Frame::default()
+        .with_size(INNER_DRY_RUN_WIN_WIDTH, DRY_RUN_INFO_FRAME_HEIGHT)
+        .with_pos(0, 0)
+        .with_align(Align::Center | Align::Inside)
+        .with_label(summary);
+    info_frame.set_label_color(C_DDLC_PINK_DARK);
+    info_frame.set_label_size(DRY_RUN_TXT_SIZE);
+
+
+    let but_ypos = INNER_DRY_RUN_WIN_HEIGHT - BUT_HEIGHT - BUT_ALERT_WIN_PADDING;
+
+    let mut buf = TextBuffer::default();
+    buf.set_text(&paths.join("\n"));
+
+    let mut txt = TextDisplay::default()
+        .with_size(
+            INNER_DRY_RUN_WIN_WIDTH - 2*INNER_WIN_CONTENT_XPADDING,
+            but_ypos - DRY_RUN_INFO_FRAME_HEIGHT - BUT_ALERT_WIN_PADDING
+        )
+        .with_pos(INNER_WIN_CONTENT_XPADDING, DRY_RUN_INFO_FRAME_HEIGHT);
+    txt.set_frame(FrameType::EngravedFrame);
+    txt.set_color(C_DDLC_WHITE_ACT);
+    txt.set_text_color(C_BLACK);
+    txt.set_text_size(DRY_RUN_TXT_SIZE);
+    txt.set_buffer(buf);
+
+
+    let mut proceed_but = _build_button_base(
+        BUT_WIDTH,
+        BUT_HEIGHT,
+        proceed_label,
+        _handle_button,
+        _draw_button
+    );
+    proceed_but.set_pos(INNER_DRY_RUN_WIN_WIDTH/2 - BUT_WIDTH - BUT_SPACING, but_ypos);
+    proceed_but.set_callback({
+        let mut win = dry_run_win.clone();
+        let proceed = proceed.clone();
+        move |_| {
+            proceed.set(true);
+            win.hide();
+        }
+    });
+
+    let mut cancel_but = _build_button_base(
+        BUT_WIDTH,
+        BUT_HEIGHT,
+        cancel_label,
+        _handle_button,
+        _draw_button
+    );
+    cancel_but.set_pos(INNER_DRY_RUN_WIN_WIDTH/2 + BUT_SPACING, but_ypos);
+    cancel_but.set_callback({
+        let mut win = dry_run_win.clone();
+        move |_| {
+            proceed.set(false);
+            win.hide();
+        }
+    });
+
+
+    inner_win.end();
+
+    dry_run_win.end();
+    dry_run_win.hide();
+    dry_run_win.make_modal(true);
+
+    return dry_run_win;
+}
+
+
+fn _build_exit_button(sender: Sender<Message>) -> Button {
+    let mut but = build_button(get_strings().but_exit_label, sender, Message::Close);
+    but.set_pos(INNER_WIN_WIDTH-BUT_WIDTH-INNER_WIN_CONTENT_XPADDING, INNER_WIN_HEIGHT-BUT_HEIGHT-BUT_PACK_YPADDING);
+
+    return but;
+}
+
+/// Builds the abort windows
+pub fn build_abort_win(sender: Sender<Message>) -> DoubleWindow {
+    let abort_win = build_inner_win();
+    abort_win.begin();
+
+    _build_top_frame(get_strings().abort_top_frame_label);
+    _build_mid_frame(get_strings().abort_mid_frame_label);
+
+    _build_exit_button(sender);
+
+    abort_win.end();
+
+    return abort_win;
+}
+
+/// Builds the done windows
+/// NOTE: the kept archives frame is filled in by the app
+pub fn build_done_win(sender: Sender<Message>, kept_archives_frame: &Frame) -> DoubleWindow {
+    let mut done_win = _build_done_win_base(
+        sender,
+        get_strings().done_mid_frame_label,
+        &[
+            (get_strings().but_open_folder_label, Message::OpenFolder, true),
+            (get_strings().but_launch_game_label, Message::LaunchGame, platform::get_capabilities().launch_game)
+        ]
+    );
+    done_win.add(kept_archives_frame);
+
+    return done_win;
+}
+
+/// Builds the done windows for uninstallation
+pub fn build_uninstall_done_win(sender: Sender<Message>) -> DoubleWindow {
+    return _build_done_win_base(sender, get_strings().uninstall_done_mid_frame_label, &[]);
+}
+
+/// Builds a done window, the extra buttons are placed
+/// to the left of the exit button (right to left),
+/// the unavailable ones are grayed out
+fn _build_done_win_base(sender: Sender<Message>, mid_label: &str, extra_buts: &[(&str, Message, bool)]) -> DoubleWindow {
+    let done_win = build_inner_win();
+    done_win.begin();
+
+    _build_top_frame(get_strings().done_top_frame_label);
+    _build_mid_frame(mid_label);
+
+    let exit_but = _build_exit_button(sender);
+    let mut but_xpos = exit_but.x();
+    for (label, msg, is_available) in extra_buts {
+        but_xpos -= BUT_WIDTH + BUT_SPACING;
+        let mut but = build_button(label, sender, *msg);
+        but.set_pos(but_xpos, exit_but.y());
+        if !is_available {
+            but.deactivate();
+            but.set_tooltip(get_strings().unsupported_feature_tooltip);
+        }
+    }
 
     done_win.end();
 
@@ -2,31 +2,145 @@
 
 pub mod builder;
 pub mod state;
+pub mod strings;
 pub mod styles;
 
 
-use std::{thread, mem, path::PathBuf};
+use std::{thread, mem, path::{Path, PathBuf}, time::Duration};
 
 use fltk::{
     app::{
+        self,
         App as fltkApp,
+        TimeoutHandle,
         channel,
         Sender,
         Receiver
     },
+    button::Button,
     text::TextBuffer,
+    frame::Frame,
+    menu::Choice,
     misc::Progress,
     prelude::{
         WidgetExt,
+        WidgetBase,
         GroupExt,
+        MenuExt,
+        WindowExt
     },
     window::DoubleWindow
 };
 
-use state::{ThreadSafeState, build_thread_safe_state};
-use crate::{Message, InstallResult};
-use super::{audio, utils, errors};
-use errors::InstallerError;
+use state::{AppState, ThreadSafeState, build_thread_safe_state};
+use crate::{config::{self, Config, WindowGeometry}, Message, InstallResult};
+use super::{audio, utils, errors, logger};
+use errors::{InstallerError, ErrorKind};
+
+
+// Ids of the linked windows
+const WELCOME_WIN_ID: usize = 0;
+const SELECT_DIR_WIN_ID: usize = 2;
+const SELECT_VERSION_WIN_ID: usize = 3;
+const OPTIONS_WIN_ID: usize = 4;
+const SUMMARY_WIN_ID: usize = 5;
+const PROGRESS_WIN_ID: usize = 6;
+
+// How long we wait for the installer thread when the user closes the window
+const SHUTDOWN_TIMEOUT: f64 = 10.0;
+
+
+/// The windows of the app and the widgets we need to update,
+/// they're rebuilt when the language changes
+struct Widgets {
+    linked_windows: [DoubleWindow; 7],
+    abort_window: DoubleWindow,
+    done_window: DoubleWindow,
+    uninstall_done_window: DoubleWindow,
+    version_choice: Choice,
+    progress_bar: Progress,
+    progress_version: Frame,
+    progress_phase: Frame,
+    progress_info: Frame,
+    pause_button: Button,
+    download_size: Frame,
+    summary_buf: TextBuffer,
+    kept_archives: Frame
+}
+
+/// Builds the windows of the app, must be called within the main window
+fn build_widgets(
+    sender: Sender<Message>,
+    state: &ThreadSafeState,
+    path_txt_buf: &TextBuffer,
+    release_notes_buf: &TextBuffer
+) -> Widgets {
+    let version_choice = builder::build_version_choice(sender);
+    let progress_bar = builder::build_progress_bar();
+    let progress_version = builder::build_progress_version_frame();
+    let progress_phase = builder::build_progress_phase_frame();
+    let progress_info = builder::build_progress_info_frame();
+    let pause_button = builder::build_pause_button(sender);
+    let download_size = builder::build_download_size_frame();
+    let summary_buf = TextBuffer::default();
+    let kept_archives = builder::build_kept_archives_frame();
+
+    let linked_windows = {
+        let s = state.lock().unwrap();
+        let is_dark_theme = s.get_theme() == styles::Theme::Dark;
+        let is_dlx_version = s.get_deluxe_ver_flag();
+        let install_spr = s.get_install_spr_flag();
+        let backup = s.get_backup_flag();
+        let shortcut = s.get_shortcut_flag();
+        let keep_archives = s.get_keep_archives_flag();
+        let dry_run = s.get_dry_run_flag();
+        let prerelease = s.get_prerelease_flag();
+        let speed_limit = s.get_speed_limit_mb();
+        [
+            builder::build_welcome_win(sender, is_dark_theme, release_notes_buf.clone()),
+            builder::build_license_win(sender),
+            builder::build_select_dir_win(sender, path_txt_buf.clone()),
+            builder::build_select_version_win(sender, &version_choice),
+            builder::build_options_win(
+                sender,
+                is_dlx_version,
+                install_spr,
+                backup,
+                shortcut,
+                keep_archives,
+                dry_run,
+                prerelease,
+                speed_limit,
+                &download_size
+            ),
+            builder::build_summary_win(sender, summary_buf.clone()),
+            builder::build_propgress_win(
+                sender,
+                &progress_bar,
+                &progress_version,
+                &progress_phase,
+                &progress_info,
+                &pause_button
+            )
+        ]
+    };
+
+    return Widgets {
+        linked_windows,
+        abort_window: builder::build_abort_win(sender),
+        done_window: builder::build_done_win(sender, &kept_archives),
+        uninstall_done_window: builder::build_uninstall_done_win(sender),
+        version_choice,
+        progress_bar,
+        progress_version,
+        progress_phase,
+        progress_info,
+        pause_button,
+        download_size,
+        summary_buf,
+        kept_archives
+    };
+}
 
 
 /// A struct representing our app
@@ -43,12 +157,15 @@ pub struct InstallerApp {
     main_window: DoubleWindow,
     // The windows the user can switch
     // using the back & continue buttons
-    linked_windows: [DoubleWindow; 5],
+    linked_windows: [DoubleWindow; 7],
     // Current window id
     current_window_id: usize,
     // These windows need to be available directly
     abort_window: DoubleWindow,
     done_window: DoubleWindow,
+    uninstall_done_window: DoubleWindow,
+    // Shown instead of the main window in the compact mode
+    compact_window: Option<DoubleWindow>,
 
     // Audio manager, option because audio might not work
     audio_manager: Option<audio::AudioManager>,
@@ -56,41 +173,176 @@ pub struct InstallerApp {
     // Handle to the installer thread, option because we might not start it/close early
     installer_th_handle: Option<thread::JoinHandle<InstallResult>>,
 
+    // Whether we've started fetching the list of releases
+    releases_fetch_started: bool,
+    // Whether the release notes request has finished (even if it failed)
+    release_notes_loaded: bool,
+
     // These need to be updated
     path_txt_buf: TextBuffer,
-    progress_bar: Progress
+    release_notes_buf: TextBuffer,
+    version_choice: Choice,
+    progress_bar: Progress,
+    progress_version: Frame,
+    progress_phase: Frame,
+    progress_info: Frame,
+    pause_button: Button,
+    download_size: Frame,
+    summary_buf: TextBuffer,
+    kept_archives: Frame,
+    // The timer animating the progress bar when the progress is unknown
+    progress_marquee: Option<TimeoutHandle>,
+    // Data shown under the progress bar
+    retry_attempt: Option<u32>,
+    throttled_delay: Option<Duration>,
+    download_speed: Option<f64>,
+    download_eta: Option<Duration>,
+    // Bytes downloaded across all downloads and their total size
+    downloaded_bytes: Option<(u64, u64)>,
+    extracting_file: Option<String>,
+    // Set when the user closed the window while the installer was running
+    is_closing: bool
 }
 
-impl InstallerApp {
-    /// Creates a new App, must be used at runtime
-    pub fn new() -> Self {
-        let (sender, receiver): (Sender<Message>, Receiver<Message>) = channel();
+/// Builds the error message for the user: what went wrong, how to fix it,
+/// and the technical details if we have the error
+fn _format_error_msg(kind: ErrorKind, error: Option<&InstallerError>) -> String {
+    let strings = strings::get_strings();
+    let mut msg = String::from(strings.get_error_msg(kind));
+    if let Some(hint) = strings.get_error_hint(kind) {
+        msg.push('\n');
+        msg.push_str(hint);
+    }
+    if let Some(e) = error {
+        msg.push_str(&format!("\n\n{}\n{e}", strings.error_details_label));
+    }
+    return msg;
+}
 
-        let state = build_thread_safe_state();
+/// Returns the info of the release we're going to install, None if we don't know it yet
+fn _find_selected_release(app_state: &AppState) -> Option<&utils::ReleaseInfo> {
+    let allow_prerelease = app_state.get_prerelease_flag();
+    return app_state.get_releases().and_then(
+        |releases| match app_state.get_release_tag() {
+            Some(tag) => releases.iter().find(|release| release.tag == tag),
+            // Same as the installer, the newest one we're allowed to use
+            None => releases.iter().find(|release| allow_prerelease || !release.prerelease)
+        }
+    );
+}
 
-        let path_txt_buf = TextBuffer::default();
-        let progress_bar = builder::build_progress_bar();
+/// Returns the download links of the assets we're going to install,
+/// empty if the releases haven't been loaded
+fn _get_selected_links(app_state: &AppState) -> Vec<String> {
+    let release = match _find_selected_release(app_state) {
+        Some(release) => release,
+        None => return Vec::new()
+    };
+    let mut links = Vec::new();
+    if !app_state.get_spr_only_flag() {
+        match app_state.get_deluxe_ver_flag() {
+            true => links.extend(release.dlx_links.iter().cloned()),
+            false => links.extend(release.def_links.iter().cloned())
+        };
+    }
+    if app_state.get_install_spr_flag() || app_state.get_spr_only_flag() {
+        let names: Vec<&str> = release.spr_bundles.iter().map(|bundle| bundle.name.as_str()).collect();
+        let selected = utils::select_spr_bundles(&names, app_state.get_spr_bundles());
+        links.extend(
+            release.spr_bundles.iter()
+                .filter(|bundle| selected.contains(&bundle.name.as_str()))
+                .filter_map(|bundle| bundle.dl_link.clone())
+        );
+    }
+    return links;
+}
 
-        let main_window = builder::build_outer_win(sender, &state);
-        main_window.begin();
+/// Returns the id of the window after the given one, None if it's the last one
+fn _get_next_window_id(current_id: usize, total: usize) -> Option<usize> {
+    return current_id.checked_add(1).filter(|&new_id| new_id < total);
+}
 
-        let linked_windows = {
-            let s = state.lock().unwrap();
-            let is_dlx_version = s.get_deluxe_ver_flag();
-            let install_spr = s.get_install_spr_flag();
-            [
-                builder::build_welcome_win(sender),
-                builder::build_license_win(sender),
-                builder::build_select_dir_win(sender, path_txt_buf.clone()),
-                builder::build_options_win(sender, is_dlx_version, install_spr),
-                builder::build_propgress_win(sender, &progress_bar)
-            ]
+/// Returns the id of the window before the given one, None if it's the first one
+fn _get_previous_window_id(current_id: usize) -> Option<usize> {
+    return current_id.checked_sub(1);
+}
+
+/// Deactivates the widgets with the given label in the window (and its groups),
+/// e.g. the nav buttons that have nowhere to go
+fn _deactivate_labeled_widgets(group: &impl GroupExt, label: &str) {
+    for i in 0..group.children() {
+        let mut child = match group.child(i) {
+            Some(child) => child,
+            None => continue
         };
+        if let Some(inner_group) = child.as_group() {
+            _deactivate_labeled_widgets(&inner_group, label);
+        }
+        else if child.label() == label {
+            child.deactivate();
+        }
+    }
+}
 
-        let abort_window = builder::build_abort_win(sender);
-        let done_window = builder::build_done_win(sender);
+/// Returns the size of the spritepack bundles we're going to install
+fn _get_selected_spr_size(release: &utils::ReleaseInfo, app_state: &AppState) -> Option<u64> {
+    let names: Vec<&str> = release.spr_bundles.iter().map(|bundle| bundle.name.as_str()).collect();
+    let selected = utils::select_spr_bundles(&names, app_state.get_spr_bundles());
+    return release.spr_bundles.iter()
+        .filter(|bundle| selected.contains(&bundle.name.as_str()))
+        .map(|bundle| bundle.size)
+        .sum();
+}
+
+/// Formats the error with the info we need in a bug report,
+/// any credentials are removed
+fn _format_error_details(error: &InstallerError, state: &ThreadSafeState) -> String {
+    let app_state = state.lock().unwrap();
+    let repo = app_state.get_repo();
+    let (release, url) = match (app_state.get_local_archive(), app_state.get_release_tag()) {
+        (Some(path), _) => (format!("local archive {}", path.display()), None),
+        (None, Some(tag)) => (tag.to_owned(), Some(format!("https://github.com/{repo}/releases/tag/{tag}"))),
+        (None, None) => (String::from("latest"), Some(format!("https://github.com/{repo}/releases/latest")))
+    };
+    let mut details = format!(
+        "Installer version: {}\nOS: {} ({})\nRepository: {repo}\nRelease: {release}\n",
+        crate::VERSION.unwrap_or(crate::DEF_VERSION),
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    if let Some(url) = url {
+        details.push_str(&format!("Release url: {url}\n"));
+    }
+    details.push_str(&format!("Error: {error}\nDebug: {error:?}\n"));
+    return utils::redact_text(&details, app_state.get_proxy());
+}
+
+
+impl InstallerApp {
+    /// Creates a new App using the given config, must be used at runtime
+    pub fn new(config: &Config) -> Self {
+        let (sender, receiver): (Sender<Message>, Receiver<Message>) = channel();
+
+        let state = build_thread_safe_state(config);
+        // Must be set before building the windows
+        {
+            let s = state.lock().unwrap();
+            styles::set_theme(s.get_theme());
+            strings::set_language(s.get_language().unwrap_or_else(strings::Language::detect));
+        }
 
+        let path_txt_buf = TextBuffer::default();
+        let release_notes_buf = TextBuffer::default();
+
+        let mut main_window = builder::build_outer_win(sender);
+        main_window.begin();
+        let widgets = build_widgets(sender, &state, &path_txt_buf, &release_notes_buf);
         main_window.end();
+        // After the widgets, so they scale with the window
+        let geometry = state.lock().unwrap().get_window_geometry().copied();
+        if let Some(geometry) = geometry {
+            builder::restore_outer_win_geometry(&mut main_window, &geometry);
+        }
 
         let audio_manager = match audio::play_theme() {
             Ok(s) => Some(s),
@@ -106,24 +358,119 @@ impl InstallerApp {
             sender,
             receiver,
             main_window,
-            linked_windows,
+            linked_windows: widgets.linked_windows,
             current_window_id: 0,
-            abort_window,
-            done_window,
+            abort_window: widgets.abort_window,
+            done_window: widgets.done_window,
+            uninstall_done_window: widgets.uninstall_done_window,
+            compact_window: None,
             audio_manager,
             installer_th_handle: None,
+            releases_fetch_started: false,
+            release_notes_loaded: false,
             path_txt_buf,
-            progress_bar
+            release_notes_buf,
+            version_choice: widgets.version_choice,
+            progress_bar: widgets.progress_bar,
+            progress_version: widgets.progress_version,
+            progress_phase: widgets.progress_phase,
+            progress_info: widgets.progress_info,
+            pause_button: widgets.pause_button,
+            download_size: widgets.download_size,
+            summary_buf: widgets.summary_buf,
+            kept_archives: widgets.kept_archives,
+            progress_marquee: None,
+            retry_attempt: None,
+            throttled_delay: None,
+            download_speed: None,
+            download_eta: None,
+            downloaded_bytes: None,
+            extracting_file: None,
+            is_closing: false
         };
         // Imprortant to set the dir again to update the text disp buffer
-        installer.set_extraction_dir(utils::get_cwd());
+        // Prefer the last used dir, then the Steam install
+        let config_dir = config.extraction_dir.as_deref().and_then(|dir| utils::normalize_path(dir).ok());
+        let extraction_dir = match config_dir.filter(|dir| dir.is_dir()) {
+            Some(dir) => dir,
+            None => utils::find_steam_ddlc().unwrap_or_else(utils::get_cwd)
+        };
+        installer.set_extraction_dir(extraction_dir);
+        // Show the placeholder until the notes are loaded
+        installer.update_release_notes();
+        utils::fetch_release_notes_in_thread(sender, &installer.state);
 
         return installer;
     }
 
+    /// Saves the user preferences into the config file
+    pub fn save_config(&self) {
+        // Keep the old place if the window has never been shown, e.g. in compact mode
+        if self.main_window.shown() {
+            let geometry = WindowGeometry {
+                x: self.main_window.x(),
+                y: self.main_window.y(),
+                width: self.main_window.width(),
+                height: self.main_window.height()
+            };
+            self.state.lock().unwrap().set_window_geometry(Some(geometry));
+        }
+        let config = self.state.lock().unwrap().to_config();
+        if let Err(e) = config::save_config(&config) {
+            eprintln!("Failed to save config: {e}");
+        }
+    }
+
     /// Shows the main window, thus renders the app
     pub fn show(&mut self) {
-        self.main_window.show();
+        match self.compact_window {
+            Some(ref mut compact_window) => compact_window.show(),
+            None => self.main_window.show()
+        };
+    }
+
+    /// Returns true if the user prefers the compact mode
+    pub fn get_compact_mode_flag(&self) -> bool {
+        return self.state.lock().unwrap().get_compact_mode_flag();
+    }
+
+    /// Skips the wizard and starts installing using the saved preferences,
+    /// only the compact window is shown until the installation ends,
+    /// returns false if the saved dir can't be used
+    pub fn enter_compact_mode(&mut self) -> bool {
+        {
+            let mut app_state = self.state.lock().unwrap();
+            if !utils::is_valid_ddlc_dir(app_state.get_extraction_dir()) {
+                return false;
+            }
+            // Repeat users are updating their install, no need to ask
+            app_state.set_clean_install_flag(false);
+            app_state.set_dry_run_flag(false);
+            // The partial downloads are verified anyway
+            app_state.set_resume_flag(true);
+        }
+        println!("Starting in compact mode...");
+        self.change_window(PROGRESS_WIN_ID);
+        self.compact_window = Some(
+            builder::build_compact_progress_win(self.sender, &self.progress_bar, &self.progress_phase)
+        );
+        self.start_installation();
+        return true;
+    }
+
+    /// Replaces the compact window with the main one,
+    /// does nothing if we're not in the compact mode
+    fn exit_compact_mode(&mut self) {
+        if let Some(mut compact_window) = self.compact_window.take() {
+            builder::attach_progress_widgets(
+                &mut self.linked_windows[PROGRESS_WIN_ID],
+                &self.progress_bar,
+                &self.progress_phase
+            );
+            compact_window.hide();
+            DoubleWindow::delete(compact_window);
+            self.main_window.show();
+        }
     }
 
     /// Blocks current thread and runs event loop of the app
@@ -132,24 +479,238 @@ impl InstallerApp {
             if let Some(msg) = self.receiver.recv() {
                 match msg {
                     Message::UpdateProgressBar(val) => {
-                        self.progress_bar.set_value(val);
+                        self.stop_progress_marquee();
+                        self.set_progress_value(val);
+                        // We're making progress, so any retry info is outdated
+                        self.retry_attempt = None;
+                        self.throttled_delay = None;
+                        self.update_progress_info();
+                    },
+                    Message::ProgressIndeterminate => {
+                        self.start_progress_marquee();
+                    },
+                    Message::RetryingDownload(attempt) => {
+                        self.retry_attempt = Some(attempt);
+                        self.throttled_delay = None;
+                        self.update_progress_info();
+                    },
+                    Message::Throttled(delay) => {
+                        self.throttled_delay = Some(delay);
+                        self.retry_attempt = None;
+                        self.update_progress_info();
+                    },
+                    Message::DownloadSpeed(speed) => {
+                        self.download_speed = Some(speed);
+                        self.update_progress_info();
+                    },
+                    Message::Eta(eta) => {
+                        self.download_eta = Some(eta);
+                        self.update_progress_info();
+                    },
+                    Message::BytesProgress { done, total } => {
+                        self.downloaded_bytes = Some((done, total));
+                        self.update_progress_info();
                     },
                     Message::Close => {
                         break;
                     },
+                    Message::WindowClose => {
+                        let is_running = self.state.lock().unwrap().get_worker_running_flag();
+                        if self.is_closing {
+                            // Second time, or the thread is stuck, don't wait for it anymore
+                            println!("Force quitting...");
+                            self.installer_th_handle = None;
+                            break;
+                        }
+                        else if is_running {
+                            println!("Stopping the installer before quitting...");
+                            self.is_closing = true;
+                            self.abort_installation();
+                            self.exit_compact_mode();
+                            self.hide_current_window();
+                            self.abort_window.show();
+                            // The thread might be stuck on a network call, don't wait forever
+                            let sender = self.sender;
+                            app::add_timeout3(
+                                SHUTDOWN_TIMEOUT,
+                                move |_| sender.send(Message::WindowClose)
+                            );
+                        }
+                        else {
+                            // We first show the abort screen, then quit
+                            match self.state.lock().unwrap().get_abort_flag() {
+                                false => self.sender.send(Message::Abort),
+                                true => break
+                            };
+                        }
+                    },
+                    Message::WorkerFinished => {
+                        // The thread has cleaned up after itself, safe to quit now
+                        if self.is_closing {
+                            self.cleanup_th_handle();
+                            // Same as cancelling, don't leave the partial files in the temp dir
+                            utils::discard_partial_downloads(&self.state);
+                            break;
+                        }
+                    },
                     Message::NextPage => {
-                        self.show_next_window();
+                        let is_uninstalling = self.state.lock().unwrap().get_uninstall_flag();
+                        let is_verifying = self.state.lock().unwrap().get_verify_flag();
+                        let is_local = self.state.lock().unwrap().get_local_archive().is_some();
+                        let is_spr_only = self.state.lock().unwrap().get_spr_only_flag();
+                        if self.current_window_id == SELECT_DIR_WIN_ID && !self.check_selected_dir() {
+                            continue;
+                        }
+                        // In uninstall mode the select dir window is the last one
+                        if is_uninstalling && self.current_window_id == SELECT_DIR_WIN_ID {
+                            self.start_uninstallation();
+                        }
+                        else if is_verifying && self.current_window_id == SELECT_DIR_WIN_ID {
+                            self.start_verification();
+                        }
+                        // Same for installing from a file, there's nothing to pick
+                        else if is_local && self.current_window_id == SELECT_DIR_WIN_ID {
+                            self.sender.send(Message::Install);
+                        }
+                        // Only spritepacks, the options don't apply
+                        else if is_spr_only && self.current_window_id == SELECT_VERSION_WIN_ID {
+                            self.change_window(SUMMARY_WIN_ID);
+                        }
+                        else {
+                            if !self.show_next_window() {
+                                log::debug!("Ignoring NextPage on the last page");
+                                self.deactivate_nav_button(strings::get_strings().but_continue_label);
+                            }
+                        }
                     },
                     Message::PrevPage => {
-                        self.show_previous_window();
+                        let mut app_state = self.state.lock().unwrap();
+                        // In uninstall mode we came here right from the welcome window,
+                        // same for installing from a file
+                        let is_special_mode = app_state.get_uninstall_flag()
+                            || app_state.get_local_archive().is_some()
+                            || app_state.get_spr_only_flag()
+                            || app_state.get_verify_flag()
+                            || app_state.get_copy_source().is_some();
+                        if is_special_mode && self.current_window_id == SELECT_DIR_WIN_ID {
+                            app_state.set_uninstall_flag(false);
+                            app_state.set_verify_flag(false);
+                            app_state.set_local_archive(None);
+                            app_state.set_spr_only_flag(false);
+                            app_state.set_copy_source(None);
+                            drop(app_state);
+                            self.change_window(WELCOME_WIN_ID);
+                        }
+                        // We skipped the options on the way here
+                        else if app_state.get_spr_only_flag() && self.current_window_id == SUMMARY_WIN_ID {
+                            drop(app_state);
+                            self.change_window(SELECT_VERSION_WIN_ID);
+                        }
+                        else {
+                            drop(app_state);
+                            if !self.show_previous_window() {
+                                log::debug!("Ignoring PrevPage on the first page");
+                                self.deactivate_nav_button(strings::get_strings().but_back_label);
+                            }
+                        }
+                    },
+                    Message::Uninstall => {
+                        println!("Uninstall mode...");
+                        self.state.lock().unwrap().set_uninstall_flag(true);
+                        self.change_window(SELECT_DIR_WIN_ID);
+                    },
+                    Message::Verify => {
+                        println!("Verify mode...");
+                        self.state.lock().unwrap().set_verify_flag(true);
+                        self.change_window(SELECT_DIR_WIN_ID);
+                    },
+                    Message::SpritepacksOnly => {
+                        println!("Spritepacks only mode...");
+                        self.state.lock().unwrap().set_spr_only_flag(true);
+                        self.change_window(SELECT_DIR_WIN_ID);
+                    },
+                    Message::CopyDdlc => {
+                        let source = utils::run_select_dir_dlg(strings::get_strings().copy_source_dlg_prompt);
+                        if !utils::is_valid_ddlc_dir(&source) {
+                            utils::run_alert_dlg(strings::get_strings().invalid_copy_source_dlg_msg);
+                            continue;
+                        }
+                        println!("Fresh copy mode, copying {}...", source.display());
+                        self.state.lock().unwrap().set_copy_source(Some(source));
+                        self.change_window(SELECT_DIR_WIN_ID);
+                    },
+                    Message::About => {
+                        let app_state = self.state.lock().unwrap();
+                        let repo = app_state.get_repo().to_owned();
+                        let latest_tag = app_state.get_release_notes().map(|notes| notes.tag.clone());
+                        drop(app_state);
+                        utils::run_about_dlg(&repo, latest_tag.as_deref());
+                    },
+                    Message::ViewLicense => {
+                        utils::run_license_dlg();
+                    },
+                    Message::InstallFromFile => {
+                        if let Some(zip_path) = utils::run_select_zip_dlg(strings::get_strings().sel_zip_dlg_prompt) {
+                            println!("Install from file mode...");
+                            self.state.lock().unwrap().set_local_archive(Some(zip_path));
+                            self.change_window(SELECT_DIR_WIN_ID);
+                        }
                     },
                     Message::SelectDir => {
-                        let selected_dir = utils::run_select_dir_dlg(styles::SEL_DIR_DLG_PROMPT);
-                        if !utils::is_valid_ddlc_dir(&selected_dir) {
-                            utils::run_msg_dlg("Attention!\nSelected directory doesn't appear to be\na valid DDLC directory");
+                        let selected_dir = utils::run_select_dir_dlg(strings::get_strings().sel_dir_dlg_prompt);
+                        // Changing the dir from the summary, it's checked the same way the next page does
+                        if self.current_window_id == SUMMARY_WIN_ID {
+                            if selected_dir.as_os_str().is_empty() {
+                                continue;
+                            }
+                            self.set_extraction_dir(selected_dir);
+                            match self.check_selected_dir() {
+                                true => self.update_summary(),
+                                false => {
+                                    self.change_window(SELECT_DIR_WIN_ID);
+                                }
+                            };
+                            continue;
+                        }
+                        let is_copying = self.state.lock().unwrap().get_copy_source().is_some();
+                        if !is_copying && !utils::is_valid_ddlc_dir(&selected_dir) {
+                            utils::run_msg_dlg(strings::get_strings().invalid_dir_dlg_msg);
                         }
                         self.set_extraction_dir(selected_dir);
                     },
+                    Message::ReleaseNotesLoaded => {
+                        self.release_notes_loaded = true;
+                        self.update_release_notes();
+                    },
+                    Message::ReleasesLoaded => {
+                        // Allow to try again next time if we failed
+                        let has_releases = self.state.lock().unwrap().get_releases().map(|releases| !releases.is_empty());
+                        match has_releases {
+                            None => self.releases_fetch_started = false,
+                            Some(false) => utils::run_alert_dlg(strings::get_strings().no_releases_dlg_msg),
+                            Some(true) => {}
+                        };
+                        self.update_version_choice();
+                        self.update_download_size();
+                    },
+                    Message::VersionSelected => {
+                        let id = self.version_choice.value();
+                        let mut app_state = self.state.lock().unwrap();
+                        // The first item is always the latest version
+                        let tag = match id {
+                            id if id <= 0 => None,
+                            id => app_state.get_releases()
+                                .and_then(|releases| releases.get(id as usize - 1))
+                                .map(|release| release.tag.clone())
+                        };
+                        match tag {
+                            Some(ref tag) => println!("Using version {tag}..."),
+                            None => println!("Using latest version...")
+                        };
+                        app_state.set_release_tag(tag);
+                        drop(app_state);
+                        self.update_download_size();
+                    },
                     Message::DlxVersionCheck => {
                         let mut app_state = self.state.lock().unwrap();
                         app_state.invert_deluxe_ver_flag();
@@ -157,6 +718,8 @@ impl InstallerApp {
                             true => println!("Using deluxe version..."),
                             false => println!("Using standard version...")
                         };
+                        drop(app_state);
+                        self.update_download_size();
                     },
                     Message::InstallSprCheck => {
                         let mut app_state = self.state.lock().unwrap();
@@ -165,7 +728,117 @@ impl InstallerApp {
                             true => println!("Including spritepacks..."),
                             false => println!("Excluding spritepacks...")
                         };
+                        drop(app_state);
+                        self.update_download_size();
                     }
+                    Message::BackupCheck => {
+                        let mut app_state = self.state.lock().unwrap();
+                        app_state.invert_backup_flag();
+                        match app_state.get_backup_flag() {
+                            true => println!("Backing up game folder..."),
+                            false => println!("Not backing up game folder...")
+                        };
+                    },
+                    Message::ShortcutCheck => {
+                        let mut app_state = self.state.lock().unwrap();
+                        app_state.invert_shortcut_flag();
+                        match app_state.get_shortcut_flag() {
+                            true => println!("Creating a desktop shortcut..."),
+                            false => println!("Not creating a desktop shortcut...")
+                        };
+                    },
+                    Message::KeepArchivesCheck => {
+                        let mut app_state = self.state.lock().unwrap();
+                        app_state.invert_keep_archives_flag();
+                        match app_state.get_keep_archives_flag() {
+                            true => println!("Keeping the downloaded archives..."),
+                            false => println!("Not keeping the downloaded archives...")
+                        };
+                    },
+                    Message::DryRunCheck => {
+                        let mut app_state = self.state.lock().unwrap();
+                        app_state.invert_dry_run_flag();
+                        match app_state.get_dry_run_flag() {
+                            true => println!("Doing a dry run..."),
+                            false => println!("Not doing a dry run...")
+                        };
+                    },
+                    Message::PrereleaseCheck => {
+                        let mut app_state = self.state.lock().unwrap();
+                        app_state.invert_prerelease_flag();
+                        match app_state.get_prerelease_flag() {
+                            true => println!("Including pre-release versions..."),
+                            false => println!("Excluding pre-release versions...")
+                        };
+                        drop(app_state);
+                        self.update_download_size();
+                    },
+                    Message::DarkThemeCheck => {
+                        let theme = {
+                            let mut app_state = self.state.lock().unwrap();
+                            app_state.invert_theme();
+                            app_state.get_theme()
+                        };
+                        styles::set_theme(theme);
+                        app::redraw();
+                        match theme {
+                            styles::Theme::Dark => println!("Using dark theme..."),
+                            styles::Theme::Light => println!("Using light theme...")
+                        };
+                    },
+                    Message::LanguageSelected(language) => {
+                        println!("Using language: {}", language.get_name());
+                        self.state.lock().unwrap().set_language(Some(language));
+                        strings::set_language(language);
+                        self.rebuild_widgets();
+                    },
+                    Message::SpeedLimitChanged(limit) => {
+                        match limit {
+                            Some(limit) => println!("Limiting download speed to {limit} MB/s..."),
+                            None => println!("Not limiting download speed...")
+                        };
+                        self.state.lock().unwrap().set_speed_limit_mb(limit);
+                    },
+                    Message::ProxySettings => {
+                        let current_url = self.state.lock().unwrap().get_proxy().url.clone().unwrap_or_default();
+                        if let Some(url) = utils::run_proxy_dlg(&current_url) {
+                            let url = match url.is_empty() {
+                                true => None,
+                                false => Some(url)
+                            };
+                            match &url {
+                                Some(url) => println!("Using proxy: {url}"),
+                                None => println!("Using proxy from env vars (if any)...")
+                            };
+                            self.state.lock().unwrap().set_proxy_url(url);
+                        }
+                    },
+                    Message::SprBundles => {
+                        let bundles = _find_selected_release(&self.state.lock().unwrap())
+                            .map(|release| release.spr_bundles.clone())
+                            .unwrap_or_default();
+                        // Nothing to pick from, or the releases haven't been loaded yet
+                        if bundles.len() < 2 {
+                            utils::run_msg_dlg(strings::get_strings().spr_bundles_unavailable_dlg_msg);
+                            continue;
+                        }
+                        let names: Vec<&str> = bundles.iter().map(|bundle| bundle.name.as_str()).collect();
+                        let selected: Vec<String> = utils::select_spr_bundles(&names, self.state.lock().unwrap().get_spr_bundles())
+                            .into_iter()
+                            .map(String::from)
+                            .collect();
+                        let subfolders = self.state.lock().unwrap().get_spr_subfolders_flag();
+                        if let Some((picked, subfolders)) = utils::run_spr_bundles_dlg(&bundles, &selected, subfolders) {
+                            // Nothing picked means the default bundle
+                            let picked = Some(picked).filter(|picked| !picked.is_empty());
+                            println!("Spritepack bundles: {picked:?}, separate folders: {subfolders}");
+                            let mut app_state = self.state.lock().unwrap();
+                            app_state.set_spr_bundles(picked);
+                            app_state.set_spr_subfolders_flag(subfolders);
+                            drop(app_state);
+                            self.update_download_size();
+                        }
+                    },
                     Message::VolumeCheck => {
                         if let Some(ref am) = self.audio_manager {
                             if am.get_volume() == 0.0{
@@ -179,84 +852,629 @@ impl InstallerApp {
                         }
                     }
                     Message::Install => {
-                        let app_state = self.state.lock().unwrap();
-                        // We warn the user again if the extraction dir looks wrong
-                        if !utils::is_valid_ddlc_dir(app_state.get_extraction_dir()) {
-                            utils::run_msg_dlg("Attention!\nInstalling into a non-DDLC directory");
+                        let mut app_state = self.state.lock().unwrap();
+                        // We warn the user again if the extraction dir looks wrong,
+                        // unless they already chose to continue
+                        // NOTE: the copy becomes a DDLC dir, it's empty for now
+                        let is_copying = app_state.get_copy_source().is_some();
+                        if !is_copying && !app_state.get_invalid_dir_confirmed() && !utils::is_valid_ddlc_dir(app_state.get_extraction_dir()) {
+                            utils::run_msg_dlg(strings::get_strings().non_ddlc_dir_dlg_msg);
                         }
-                        // We also need to move to the next window
-                        self.sender.send(Message::NextPage);
-                        // Consume any existing thread first
+                        // If MAS is already there, the user might want a clean install
+                        // NOTE: closing the dialogue picks update since it's the safe option
+                        // NOTE: in spritepacks only mode we keep MAS as it is
+                        let existing_install = match app_state.get_spr_only_flag() || is_copying {
+                            true => None,
+                            false => utils::detect_mas_install(app_state.get_extraction_dir())
+                        };
+                        let clean_install = match existing_install {
+                            Some(info) => {
+                                let found_msg = match info.version {
+                                    Some(version) => strings::get_strings().found_mas_version_dlg_msg.replace("{version}", &version),
+                                    None => String::from(strings::get_strings().found_mas_install_dlg_msg)
+                                };
+                                utils::run_choice_dlg(
+                                    &format!("{found_msg}\n{}", strings::get_strings().existing_install_dlg_msg),
+                                    strings::get_strings().but_clean_install_label,
+                                    strings::get_strings().but_update_label
+                                )
+                            },
+                            None => false
+                        };
+                        app_state.set_clean_install_flag(clean_install);
+                        // A new install, forget the previous dry run
+                        app_state.set_dry_run_report(None);
+                        let is_local = app_state.get_local_archive().is_some();
+                        // Offer to continue an interrupted download of the assets we're about to install
+                        // NOTE: declining keeps the other partials, ours get truncated once they're opened
+                        let links = _get_selected_links(&app_state);
+                        let has_partial = match links.is_empty() {
+                            // Don't know the links yet, the worker checks the sidecars anyway
+                            true => utils::has_partial_downloads(app_state.get_temp_dir()),
+                            false => utils::has_partial_download_for(app_state.get_temp_dir(), &links)
+                        };
+                        let resume = !is_local && has_partial && utils::run_choice_dlg(
+                            strings::get_strings().resume_dlg_msg,
+                            strings::get_strings().but_yes_label,
+                            strings::get_strings().but_no_label
+                        );
+                        app_state.set_resume_flag(resume);
+                        app_state.set_start_over_flag(false);
+                        // Ask about the old files again
+                        app_state.set_remove_stale_files(None);
                         // Have to drop the old ref
                         drop(app_state);
-                        self.cleanup_th_handle();
-                        // Start a new thread
-                        self.installer_th_handle = Some(
-                            utils::install_game_in_thread(self.sender, &self.state)
-                        );
+                        // We also need to move to the next window
+                        match is_local {
+                            true => {
+                                self.change_window(PROGRESS_WIN_ID);
+                            },
+                            false => self.sender.send(Message::NextPage)
+                        };
+                        self.start_installation();
                     },
                     Message::Preparing => {
                         println!("Preparing...");
-                        self.progress_bar.set_label("Preparing...");
+                        self.set_progress_phase(strings::get_strings().phase_preparing);
+                        self.set_pause_available(false);
+                        self.reset_progress_info();
+                    },
+                    Message::FetchingReleaseInfo => {
+                        println!("Fetching release info...");
+                        self.set_progress_phase(strings::get_strings().phase_fetching_release_info);
+                    },
+                    Message::Copying => {
+                        println!("Done!\nCopying DDLC...");
+                        self.set_progress_phase(strings::get_strings().phase_copying);
+                        self.set_pause_available(false);
+                        self.reset_progress_info();
+                    },
+                    Message::ReleaseResolved => {
+                        let tag = self.state.lock().unwrap().get_installing_tag().map(String::from);
+                        println!("Installing {}...", tag.as_deref().unwrap_or("an unknown release"));
+                        self.set_progress_version(tag.as_deref());
+                    },
+                    Message::BackingUp => {
+                        println!("Done!\nBacking up...");
+                        self.set_progress_phase(strings::get_strings().phase_backing_up);
+                        self.set_pause_available(false);
+                        self.reset_progress_info();
+                    },
+                    Message::RemovingOldInstall => {
+                        println!("Done!\nRemoving old installation...");
+                        self.set_progress_phase(strings::get_strings().phase_removing_old_install);
+                        self.set_pause_available(false);
+                        self.reset_progress_info();
                     },
                     Message::Downloading => {
                         println!("Done!\nDownloading...");
-                        self.progress_bar.set_label("Downloading...");
+                        self.set_progress_phase(strings::get_strings().phase_downloading);
+                        self.set_pause_available(true);
+                        self.reset_progress_info();
                     },
                     Message::Extracting => {
                         println!("Done!\nExtracting...");
-                        self.progress_bar.set_label("Extracting...");
+                        self.set_progress_phase(strings::get_strings().phase_extracting);
+                        self.set_pause_available(false);
+                        self.reset_progress_info();
                     },
                     Message::DownloadingSpr => {
                         println!("Done!\nDownloading spritepacks...");
-                        self.progress_bar.set_label("Downloading spritepacks...");
+                        self.set_progress_phase(strings::get_strings().phase_downloading_spr);
+                        self.set_pause_available(true);
+                        self.reset_progress_info();
                     },
                     Message::ExtractingSpr => {
                         println!("Done!\nExtracting spritepacks...");
-                        self.progress_bar.set_label("Extracting spritepacks...");
+                        self.set_progress_phase(strings::get_strings().phase_extracting_spr);
+                        self.set_pause_available(false);
+                        self.reset_progress_info();
+                    },
+                    Message::ExtractingFile => {
+                        self.extracting_file = self.state.lock().unwrap().get_extracting_file().map(String::from);
+                        self.update_progress_info();
+                    },
+                    Message::UsingCachedDownload => {
+                        println!("Using a cached download...");
+                        self.set_progress_phase(strings::get_strings().phase_using_cache);
+                        self.set_pause_available(false);
+                        self.reset_progress_info();
+                    },
+                    Message::Verifying => {
+                        println!("Done!\nVerifying files...");
+                        self.set_progress_phase(strings::get_strings().phase_verifying);
+                        self.set_pause_available(false);
+                        self.reset_progress_info();
+                    },
+                    Message::RemovingStaleFiles => {
+                        println!("Removing old files...");
+                        self.set_progress_phase(strings::get_strings().phase_removing_stale_files);
+                        self.set_pause_available(false);
+                        self.reset_progress_info();
                     },
                     Message::CleaningUp => {
                         println!("Done!\nCleaning up...");
-                        self.progress_bar.set_label("Cleaning up...");
+                        self.set_progress_phase(strings::get_strings().phase_cleaning_up);
+                        self.set_pause_available(false);
+                        self.reset_progress_info();
+                    },
+                    Message::Uninstalling => {
+                        println!("Uninstalling...");
+                        self.set_progress_phase(strings::get_strings().phase_uninstalling);
+                        self.set_pause_available(false);
+                        self.reset_progress_info();
                     },
-                    Message::Error => {
+                    Message::Error(kind) => {
                         println!("An error has occurred...");
                         self.abort_installation();
                         let rv = self.cleanup_th_handle();
-                        // Show the error if we can
-                        if let Some(e) = rv {
-                            utils::run_alert_dlg(&format!("{e}"));
+                        // The user wants to quit, the error is most likely from aborting
+                        if self.is_closing {
+                            break;
+                        }
+                        // Nothing has been started yet, let the user fix the connection and try again
+                        if let Some(InstallerError::NoConnectivity(_)) = rv {
+                            utils::run_alert_dlg(strings::get_strings().no_connectivity_dlg_msg);
+                            self.state.lock().unwrap().set_abort_flag(false);
+                            self.change_window(OPTIONS_WIN_ID);
+                            continue;
+                        }
+                        // Nothing to install, no need for the log
+                        if let Some(InstallerError::NoReleasesAvailable(_)) = rv {
+                            utils::run_alert_dlg(strings::get_strings().no_releases_dlg_msg);
+                            self.sender.send(Message::Close);
+                            continue;
+                        }
+                        // Some errors are worth another try
+                        if let Some(e) = &rv {
+                            let mode = match e.is_retriable() {
+                                true => utils::run_retry_dlg(strings::get_strings().retry_dlg_msg),
+                                false => None
+                            };
+                            if let Some(mode) = mode {
+                                self.retry_installation(mode);
+                                continue;
+                            }
+                        }
+                        let msg = _format_error_msg(kind, rv.as_ref());
+                        let details = rv.as_ref().map(|e| _format_error_details(e, &self.state));
+                        match logger::get_install_log_path() {
+                            Some(log_path) => utils::run_error_dlg(&msg, &log_path, details.as_deref()),
+                            None => utils::run_alert_dlg(&msg)
+                        };
+                        // Let the user pick another dir, the downloads are kept
+                        if rv.map(|e| e.needs_other_dir()).unwrap_or(false) {
+                            self.state.lock().unwrap().set_abort_flag(false);
+                            self.change_window(SELECT_DIR_WIN_ID);
+                            continue;
                         }
                         // Let's just quit
                         self.sender.send(Message::Close);
                     },
+                    Message::ConfirmPopulatedDir => {
+                        let count = self.state.lock().unwrap().get_existing_entries();
+                        let confirmed = utils::run_choice_dlg(
+                            &strings::get_strings().populated_dir_dlg_msg.replace("{count}", &count.to_string()),
+                            strings::get_strings().but_continue_anyway_label,
+                            strings::get_strings().but_back_label
+                        );
+                        if confirmed {
+                            self.state.lock().unwrap().set_populated_dir_confirmed(true);
+                            continue;
+                        }
+                        // Nothing has been downloaded yet, so just let the user pick another dir
+                        println!("Going back to the directory selection...");
+                        self.abort_installation();
+                        self.cleanup_th_handle();
+                        self.state.lock().unwrap().set_abort_flag(false);
+                        self.stop_progress_marquee();
+                        self.set_progress_value(0.0);
+                        self.reset_progress_info();
+                        self.change_window(SELECT_DIR_WIN_ID);
+                    },
+                    Message::ConfirmStaleFiles => {
+                        let paths = self.state.lock().unwrap().get_stale_files().to_vec();
+                        let remove = utils::run_stale_files_dlg(&paths);
+                        if !remove {
+                            println!("Keeping the old files...");
+                        }
+                        self.state.lock().unwrap().set_remove_stale_files(Some(remove));
+                    },
+                    Message::ConfirmCreateDir => {
+                        let dir = self.state.lock().unwrap().get_extraction_dir().clone();
+                        let confirmed = utils::run_choice_dlg(
+                            &strings::get_strings().create_dir_dlg_msg.replace("{path}", &dir.display().to_string()),
+                            strings::get_strings().but_yes_label,
+                            strings::get_strings().but_back_label
+                        );
+                        if confirmed {
+                            self.state.lock().unwrap().set_create_dir_confirmed(true);
+                            continue;
+                        }
+                        // Nothing has been created yet, let the user fix the path
+                        println!("Going back to the directory selection...");
+                        self.abort_installation();
+                        self.cleanup_th_handle();
+                        self.state.lock().unwrap().set_abort_flag(false);
+                        self.stop_progress_marquee();
+                        self.set_progress_value(0.0);
+                        self.reset_progress_info();
+                        self.change_window(SELECT_DIR_WIN_ID);
+                    },
                     Message::Abort => {
                         println!("Installation has been aborted!");
                         self.abort_installation();
                         self.cleanup_th_handle();
+                        self.exit_compact_mode();
                         self.hide_current_window();
                         self.abort_window.show();
                     },
+                    Message::TogglePause => {
+                        let paused = {
+                            let mut app_state = self.state.lock().unwrap();
+                            app_state.invert_paused_flag();
+                            app_state.get_paused_flag()
+                        };
+                        match paused {
+                            true => println!("Pausing the download..."),
+                            false => println!("Resuming the download...")
+                        };
+                        self.update_pause_button();
+                        self.update_progress_info();
+                    },
+                    Message::Cancel => {
+                        println!("Installation has been cancelled!");
+                        self.cancel_installation();
+                    },
+                    Message::EnterKey => {
+                        if let Some(msg) = self.get_enter_key_message() {
+                            self.sender.send(msg);
+                        }
+                    },
+                    Message::EscapeKey => {
+                        if let Some(msg) = self.get_escape_key_message() {
+                            self.sender.send(msg);
+                        }
+                    },
+                    Message::DryRunReady => {
+                        println!("Done!\nDry run is complete!");
+                        self.cleanup_th_handle();
+                        let report = self.state.lock().unwrap().get_dry_run_report().cloned();
+                        let proceed = match report {
+                            Some(ref report) => utils::run_dry_run_dlg(report),
+                            None => false
+                        };
+                        match proceed {
+                            true => self.start_installation(),
+                            false => {
+                                self.state.lock().unwrap().set_dry_run_report(None);
+                                self.change_window(OPTIONS_WIN_ID);
+                            }
+                        };
+                    },
+                    Message::VerifyReady => {
+                        println!("Done!\nVerification is complete!");
+                        self.cleanup_th_handle();
+                        let report = self.state.lock().unwrap().get_verify_report().cloned();
+                        let repair = match report {
+                            Some(ref report) if report.is_intact() => {
+                                utils::run_msg_dlg(
+                                    &strings::get_strings().verify_ok_dlg_msg.replace("{count}", &report.checked.to_string())
+                                );
+                                false
+                            },
+                            Some(ref report) => utils::run_verify_dlg(report),
+                            None => false
+                        };
+                        match repair {
+                            true => self.start_verification(),
+                            false => {
+                                let mut app_state = self.state.lock().unwrap();
+                                app_state.set_verify_report(None);
+                                app_state.set_verify_flag(false);
+                                drop(app_state);
+                                self.set_progress_value(0.0);
+                                self.reset_progress_info();
+                                self.change_window(WELCOME_WIN_ID);
+                            }
+                        };
+                    },
                     Message::Done => {
-                        println!("Done!\nInstallation is complete!");
+                        self.exit_compact_mode();
                         self.hide_current_window();
-                        self.done_window.show();
+                        if self.state.lock().unwrap().get_uninstall_flag() {
+                            println!("Done!\nUninstallation is complete!");
+                            self.uninstall_done_window.show();
+                        }
+                        else {
+                            println!("Done!\nInstallation is complete!");
+                            self.update_kept_archives();
+                            self.done_window.show();
+                        }
+                    },
+                    Message::OpenFolder => {
+                        let dir = self.state.lock().unwrap().get_extraction_dir().clone();
+                        if let Err(e) = utils::open_in_file_manager(&dir) {
+                            eprintln!("Failed to open {}: {e}", dir.display());
+                            utils::run_alert_dlg(
+                                &strings::get_strings().open_folder_failed_dlg_msg.replace("{path}", &dir.display().to_string())
+                            );
+                        }
+                    },
+                    Message::LaunchGame => {
+                        let dir = self.state.lock().unwrap().get_extraction_dir().clone();
+                        match utils::launch_game(&dir) {
+                            Ok(_) => {
+                                println!("Launching the game...");
+                                // We're done here
+                                break;
+                            },
+                            Err(e) => {
+                                eprintln!("Failed to launch the game: {e}");
+                                utils::run_alert_dlg(
+                                    &strings::get_strings().launch_game_failed_dlg_msg.replace("{error}", &e.to_string())
+                                );
+                            }
+                        };
                     }
                 };
             }
         }
     }
 
-    /// Changes current active windows by hiding one window and showing another
-    pub fn change_window(&mut self, new_id: usize) {
+    /// Returns true if one of the final windows is being shown
+    fn is_final_window_shown(&self) -> bool {
+        return self.abort_window.visible() || self.done_window.visible() || self.uninstall_done_window.visible();
+    }
+
+    /// Returns the message the Enter key maps to in the current window
+    fn get_enter_key_message(&self) -> Option<Message> {
+        if self.is_final_window_shown() {
+            return Some(Message::Close);
+        }
+        return match self.current_window_id {
+            SUMMARY_WIN_ID => Some(Message::Install),
+            PROGRESS_WIN_ID => None,
+            _ => Some(Message::NextPage)
+        };
+    }
+
+    /// Returns the message the Escape key maps to in the current window
+    fn get_escape_key_message(&self) -> Option<Message> {
+        if self.is_final_window_shown() {
+            return Some(Message::Close);
+        }
+        return match self.current_window_id {
+            WELCOME_WIN_ID => Some(Message::Abort),
+            PROGRESS_WIN_ID => Some(Message::Cancel),
+            _ => Some(Message::PrevPage)
+        };
+    }
+
+    /// Changes current active windows by hiding one window and showing another,
+    /// returns false if there's no window with this id
+    pub fn change_window(&mut self, new_id: usize) -> bool {
         // Sanity check
-        let max_id = self.linked_windows.len()-1;
-        if new_id > max_id {
-            return;
+        if new_id >= self.linked_windows.len() {
+            return false;
         }
+        // The wizard takes over from the compact window
+        self.exit_compact_mode();
+        // Hidden windows are resized with the main one, but make sure
+        // the new window takes exactly the same place after a resize
+        let (x, y, w, h) = {
+            let current = &self.linked_windows[self.current_window_id];
+            (current.x(), current.y(), current.w(), current.h())
+        };
         self.linked_windows[self.current_window_id].hide();
+        self.linked_windows[new_id].resize(x, y, w, h);
         self.linked_windows[new_id].show();
         self.current_window_id = new_id;
+
+        // The list of releases is only needed on this window, so we fetch it lazily
+        if new_id == SELECT_VERSION_WIN_ID && !self.releases_fetch_started {
+            self.releases_fetch_started = true;
+            utils::fetch_releases_in_thread(self.sender, &self.state);
+        }
+        // The summary must reflect the latest choices
+        if new_id == SUMMARY_WIN_ID {
+            self.update_summary();
+        }
+        return true;
+    }
+
+    /// Shows the cached release notes, or a placeholder if they aren't available
+    fn update_release_notes(&mut self) {
+        let strings = strings::get_strings();
+        let text = match self.state.lock().unwrap().get_release_notes() {
+            Some(notes) if !notes.body.trim().is_empty() => {
+                format!("{}\n\n{}", strings.release_notes_title.replace("{tag}", &notes.tag), notes.body.trim())
+            },
+            _ if !self.release_notes_loaded => String::from(strings.release_notes_loading),
+            _ => String::from(strings.release_notes_unavailable)
+        };
+        self.release_notes_buf.set_text(&text);
+    }
+
+    /// Shows the total download size for the selected release and options,
+    /// empty if we don't know the release yet
+    fn update_download_size(&mut self) {
+        let text = self.format_download_size();
+        self.download_size.set_label(&text);
+        self.download_size.redraw();
+    }
+
+    /// Formats the total download size for the selected release and options,
+    /// empty if we don't know the release yet
+    fn format_download_size(&self) -> String {
+        let strings = strings::get_strings();
+        let app_state = self.state.lock().unwrap();
+        let release = _find_selected_release(&app_state);
+
+        return match release {
+            Some(release) => {
+                let format_opt_size = |size: Option<u64>| match size {
+                    Some(size) => utils::format_size(size as f64),
+                    None => String::from(strings.download_size_unknown)
+                };
+                let mas_size = match app_state.get_deluxe_ver_flag() {
+                    true => release.dlx_size,
+                    false => release.def_size
+                };
+                let spr_size = _get_selected_spr_size(release, &app_state);
+                let spr_size = strings.download_size_spr.replace("{size}", &format_opt_size(spr_size));
+                if app_state.get_spr_only_flag() {
+                    return strings.download_size_info.replace("{size}", &spr_size);
+                }
+                let mut size = strings.download_size_mas.replace("{size}", &format_opt_size(mas_size));
+                if app_state.get_install_spr_flag() {
+                    size.push_str(" + ");
+                    size.push_str(&spr_size);
+                }
+                strings.download_size_info.replace("{size}", &size)
+            },
+            None => String::new()
+        };
+    }
+
+    /// Fills the summary with the current choices
+    fn update_summary(&mut self) {
+        let strings = strings::get_strings();
+        let download_size = self.format_download_size();
+        let app_state = self.state.lock().unwrap();
+        let version = match app_state.get_release_tag() {
+            Some(tag) => tag.to_owned(),
+            None => String::from(strings.ver_choice_latest_label)
+        };
+        let edition = match app_state.get_deluxe_ver_flag() {
+            true => strings.summary_edition_deluxe,
+            false => strings.summary_edition_default
+        };
+        let spr = match app_state.get_install_spr_flag() {
+            true => strings.but_yes_label,
+            false => strings.but_no_label
+        };
+
+        let mut lines = vec![
+            strings.summary_dir_info.replace("{dir}", app_state.get_extraction_dir_str()),
+            strings.summary_version_info.replace("{version}", &version)
+        ];
+        if let Some(source) = app_state.get_copy_source() {
+            lines.insert(1, strings.summary_copy_info.replace("{dir}", &source.display().to_string()));
+        }
+        match app_state.get_spr_only_flag() {
+            true => lines.push(String::from(strings.summary_spr_only_info)),
+            false => {
+                lines.push(strings.summary_edition_info.replace("{edition}", edition));
+                lines.push(strings.summary_spr_info.replace("{spr}", spr));
+            }
+        };
+        if !download_size.is_empty() {
+            lines.push(download_size);
+        }
+        drop(app_state);
+
+        self.summary_buf.set_text(&lines.join("\n\n"));
+    }
+
+    /// Lists the archives kept after the install on the done window
+    fn update_kept_archives(&mut self) {
+        let app_state = self.state.lock().unwrap();
+        let kept = app_state.get_kept_archives();
+        let label = match kept.is_empty() {
+            true => String::new(),
+            false => {
+                let paths: Vec<String> = kept.iter().map(|path| path.display().to_string()).collect();
+                // '@' starts a symbol in fltk labels
+                strings::get_strings().kept_archives_info.replace("{paths}", &paths.join("\n").replace('@', "@@"))
+            }
+        };
+        drop(app_state);
+
+        self.kept_archives.set_label(&label);
+    }
+
+    /// Fills the version dropdown with the cached list of releases
+    fn update_version_choice(&mut self) {
+        let app_state = self.state.lock().unwrap();
+        let releases = match app_state.get_releases() {
+            Some(releases) => releases,
+            None => return
+        };
+
+        self.version_choice.clear();
+        self.version_choice.add_choice(strings::get_strings().ver_choice_latest_label);
+        let mut selected_id = 0;
+        for (i, release) in releases.iter().enumerate() {
+            // Slashes would create submenus
+            let mut label = release.tag.replace('/', "\\/");
+            if release.prerelease {
+                label.push_str(strings::get_strings().ver_choice_prerelease_suffix);
+            }
+            self.version_choice.add_choice(&label);
+            if app_state.get_release_tag() == Some(release.tag.as_str()) {
+                selected_id = i as i32 + 1;
+            }
+        }
+        self.version_choice.set_value(selected_id);
+        self.version_choice.redraw();
+    }
+
+    /// Replaces the widgets with the given ones, the old ones must've been deleted
+    /// NOTE: the destructuring has no `..`, so a new widget can't be forgotten here
+    fn set_widgets(&mut self, widgets: Widgets) {
+        let Widgets {
+            linked_windows,
+            abort_window,
+            done_window,
+            uninstall_done_window,
+            version_choice,
+            progress_bar,
+            progress_version,
+            progress_phase,
+            progress_info,
+            pause_button,
+            download_size,
+            summary_buf,
+            kept_archives
+        } = widgets;
+        self.linked_windows = linked_windows;
+        self.abort_window = abort_window;
+        self.done_window = done_window;
+        self.uninstall_done_window = uninstall_done_window;
+        self.version_choice = version_choice;
+        self.progress_bar = progress_bar;
+        self.progress_version = progress_version;
+        self.progress_phase = progress_phase;
+        self.progress_info = progress_info;
+        self.pause_button = pause_button;
+        self.download_size = download_size;
+        self.summary_buf = summary_buf;
+        self.kept_archives = kept_archives;
+    }
+
+    /// Rebuilds all windows, used to apply the new language
+    /// NOTE: this resets the app to the welcome window
+    fn rebuild_widgets(&mut self) {
+        // The new windows are built for the default size,
+        // so we scale them to the current size after
+        let (width, height) = (self.main_window.w(), self.main_window.h());
+        // This deletes the old windows
+        self.main_window.clear();
+        self.main_window.set_size(styles::WIN_WIDTH, styles::WIN_HEIGHT);
+        self.main_window.begin();
+        let widgets = build_widgets(self.sender, &self.state, &self.path_txt_buf, &self.release_notes_buf);
+        self.main_window.end();
+        self.main_window.set_size(width, height);
+
+        self.set_widgets(widgets);
+        self.current_window_id = WELCOME_WIN_ID;
+
+        self.update_version_choice();
+        self.update_release_notes();
+        self.update_download_size();
+        self.main_window.set_label(&builder::format_outer_win_title());
+        self.main_window.redraw();
     }
 
     /// Hides current window
@@ -270,18 +1488,28 @@ impl InstallerApp {
         self.linked_windows[self.current_window_id].show();
     }
 
-    /// Hides current and shows next (current id + 1) window
-    pub fn show_next_window(&mut self) {
-        self.change_window(self.current_window_id + 1);
+    /// Hides current and shows next (current id + 1) window,
+    /// returns false if we're on the last window
+    pub fn show_next_window(&mut self) -> bool {
+        return match _get_next_window_id(self.current_window_id, self.linked_windows.len()) {
+            Some(new_id) => self.change_window(new_id),
+            None => false
+        };
     }
 
-    /// Hides current and shows previous (current id - 1) window
-    pub fn show_previous_window(&mut self) {
-        // Overflow sanity check
-        if self.current_window_id == 0 {
-            return;
-        }
-        self.change_window(self.current_window_id - 1);
+    /// Hides current and shows previous (current id - 1) window,
+    /// returns false if we're on the first window
+    pub fn show_previous_window(&mut self) -> bool {
+        return match _get_previous_window_id(self.current_window_id) {
+            Some(new_id) => self.change_window(new_id),
+            None => false
+        };
+    }
+
+    /// Deactivates the nav button with the given label in the current window,
+    /// used when the button has nowhere to go
+    fn deactivate_nav_button(&mut self, label: &str) {
+        _deactivate_labeled_widgets(&self.linked_windows[self.current_window_id], label);
     }
 
     /// Joins the installer thread handle
@@ -311,6 +1539,70 @@ impl InstallerApp {
         return None;
     }
 
+    /// Starts the installer thread
+    fn start_installation(&mut self) {
+        // Consume any existing thread first
+        self.cleanup_th_handle();
+        self.set_pause_available(false);
+        self.set_progress_version(None);
+        // Start a new thread
+        self.installer_th_handle = Some(
+            utils::install_game_in_thread(self.sender, &self.state)
+        );
+    }
+
+    /// Asks the user for confirmation and starts the uninstaller thread
+    fn start_uninstallation(&mut self) {
+        let extraction_dir = self.state.lock().unwrap().get_extraction_dir().clone();
+        if utils::detect_mas_install(&extraction_dir).is_none() {
+            utils::run_msg_dlg(strings::get_strings().no_mas_install_dlg_msg);
+            return;
+        }
+        if !utils::run_choice_dlg(strings::get_strings().uninstall_dlg_msg, strings::get_strings().but_yes_label, strings::get_strings().but_no_label) {
+            return;
+        }
+
+        self.change_window(PROGRESS_WIN_ID);
+        self.cleanup_th_handle();
+        self.set_progress_version(None);
+        self.installer_th_handle = Some(
+            utils::uninstall_mas_in_thread(self.sender, &self.state)
+        );
+    }
+
+    /// Starts the thread that verifies the existing install,
+    /// or repairs it if the verification has found problems
+    fn start_verification(&mut self) {
+        self.state.lock().unwrap().set_abort_flag(false);
+        self.change_window(PROGRESS_WIN_ID);
+        self.cleanup_th_handle();
+        self.set_pause_available(false);
+        self.set_progress_version(None);
+        self.installer_th_handle = Some(
+            utils::verify_install_in_thread(self.sender, &self.state)
+        );
+    }
+
+    /// Restarts installation after an error,
+    /// either keeps the already downloaded data or removes it and downloads everything again
+    fn retry_installation(&mut self, mode: utils::RetryMode) {
+        let start_over = mode == utils::RetryMode::StartOver;
+        if start_over {
+            let temp_dir = self.state.lock().unwrap().get_temp_dir().map(Path::to_path_buf);
+            if let Err(e) = utils::clear_download_cache(temp_dir.as_deref()) {
+                eprintln!("Failed to clear download cache: {e}");
+            }
+        }
+        {
+            let mut app_state = self.state.lock().unwrap();
+            app_state.set_abort_flag(false);
+            app_state.set_resume_flag(!start_over);
+            // The worker removes the cached files of the assets it downloads
+            app_state.set_start_over_flag(start_over);
+        }
+        self.start_installation();
+    }
+
     /// Aborts installation process by setting the flag
     /// NOTE: DOES NOT JOIN THE THREAD
     fn abort_installation(&mut self) {
@@ -318,19 +1610,250 @@ impl InstallerApp {
         app_state.set_abort_flag(true);
     }
 
+    /// Stops the installer thread and returns to the directory selection,
+    /// the partial downloads are removed, so the next attempt starts from scratch
+    fn cancel_installation(&mut self) {
+        self.abort_installation();
+        // The thread polls the flag often, this shouldn't block for long
+        // NOTE: the extraction rolls back the files it created on abort
+        self.cleanup_th_handle();
+        utils::discard_partial_downloads(&self.state);
+        {
+            let mut app_state = self.state.lock().unwrap();
+            app_state.set_abort_flag(false);
+            app_state.set_resume_flag(false);
+        }
+        self.stop_progress_marquee();
+        self.set_progress_value(0.0);
+        self.reset_progress_info();
+        self.change_window(SELECT_DIR_WIN_ID);
+    }
+
+    /// Updates the progress bar value and the percentage label
+    fn set_progress_value(&mut self, value: f64) {
+        self.progress_bar.set_value(value);
+        let label = builder::format_progress_percentage(value);
+        if self.progress_bar.label() != label {
+            self.progress_bar.set_label(&label);
+        }
+    }
+
+    /// Starts the marquee-style animation of the progress bar
+    fn start_progress_marquee(&mut self) {
+        if self.progress_marquee.is_some() {
+            return;
+        }
+        self.progress_bar.set_label(strings::get_strings().pb_marquee_label);
+        self.progress_bar.set_value(0.0);
+
+        let mut bar = self.progress_bar.clone();
+        let handle = app::add_timeout3(
+            styles::PB_MARQUEE_INTERVAL,
+            move |handle| {
+                let value = bar.value() + styles::PB_MARQUEE_STEP;
+                bar.set_value(if value > bar.maximum() { bar.minimum() } else { value });
+                app::repeat_timeout3(styles::PB_MARQUEE_INTERVAL, handle);
+            }
+        );
+        self.progress_marquee = Some(handle);
+    }
+
+    /// Stops the marquee-style animation of the progress bar, if it's running
+    fn stop_progress_marquee(&mut self) {
+        if let Some(handle) = self.progress_marquee.take() {
+            app::remove_timeout3(handle);
+        }
+    }
+
+    /// Updates the text above the progress bar
+    fn set_progress_phase(&mut self, text: &str) {
+        self.progress_phase.set_label(text);
+        self.progress_phase.redraw();
+    }
+
+    /// Shows the version being installed above the phase, hides it if there's None
+    fn set_progress_version(&mut self, tag: Option<&str>) {
+        let label = match tag {
+            // Tags come from GitHub, '@' starts a symbol in fltk labels
+            Some(tag) => strings::get_strings().progress_version_info.replace("{version}", &tag.replace('@', "@@")),
+            None => String::new()
+        };
+        self.progress_version.set_label(&label);
+        self.progress_version.redraw();
+    }
+
+    /// Shows the pause button if the download can be paused now,
+    /// otherwise hides it and unpauses
+    fn set_pause_available(&mut self, available: bool) {
+        if !available {
+            self.state.lock().unwrap().set_paused_flag(false);
+            self.pause_button.hide();
+        }
+        else {
+            self.pause_button.show();
+        }
+        self.update_pause_button();
+    }
+
+    /// Updates the pause button label to match the paused flag
+    fn update_pause_button(&mut self) {
+        let label = match self.state.lock().unwrap().get_paused_flag() {
+            true => strings::get_strings().but_resume_label,
+            false => strings::get_strings().but_pause_label
+        };
+        self.pause_button.set_label(label);
+        self.pause_button.redraw();
+    }
+
+    /// Updates the text under the progress bar
+    fn update_progress_info(&mut self) {
+        let text = if self.state.lock().unwrap().get_paused_flag() {
+            String::from(strings::get_strings().paused_info)
+        }
+        else if let Some(delay) = self.throttled_delay {
+            strings::get_strings().throttled_info.replace("{delay}", &utils::format_duration(delay))
+        }
+        else if let Some(attempt) = self.retry_attempt {
+            let max_retries = self.state.lock().unwrap().get_max_retries();
+            strings::get_strings().retrying_info
+                .replace("{attempt}", &attempt.to_string())
+                .replace("{max_retries}", &max_retries.to_string())
+        }
+        else if let Some(speed) = self.download_speed {
+            // Anything longer than a day is most likely a temporary stall
+            let eta = match self.download_eta {
+                Some(eta) if eta < styles::PB_INFO_MAX_ETA => {
+                    strings::get_strings().eta_info.replace("{eta}", &utils::format_duration(eta))
+                },
+                _ => String::from(strings::get_strings().eta_calculating_info)
+            };
+            match self.format_downloaded_bytes() {
+                Some(downloaded) => format!("{}, {}/s, {}", downloaded, utils::format_size(speed), eta),
+                None => format!("{}/s, {}", utils::format_size(speed), eta)
+            }
+        }
+        else if let Some(downloaded) = self.format_downloaded_bytes() {
+            downloaded
+        }
+        else if let Some(name) = self.extracting_file.as_ref() {
+            strings::get_strings().extracting_file_info.replace("{file}", name)
+        }
+        else {
+            String::new()
+        };
+
+        if self.progress_info.label() != text {
+            self.progress_info.set_label(&text);
+            self.progress_info.redraw();
+        }
+    }
+
+    /// Clears the text under the progress bar, used when we switch phases
+    fn reset_progress_info(&mut self) {
+        self.retry_attempt = None;
+        self.throttled_delay = None;
+        self.download_speed = None;
+        self.download_eta = None;
+        self.downloaded_bytes = None;
+        self.extracting_file = None;
+        self.update_progress_info();
+    }
+
+    /// Formats the bytes downloaded across all downloads, e.g. "215 MB / 592 MB downloaded"
+    fn format_downloaded_bytes(&self) -> Option<String> {
+        let (done, total) = self.downloaded_bytes?;
+        return Some(
+            strings::get_strings().downloaded_info
+                .replace("{done}", &utils::format_size(done as f64))
+                .replace("{total}", &utils::format_size(total as f64))
+        );
+    }
+
     /// Updates the extraction dir and text display with the path
     fn set_extraction_dir(&mut self, new_dir: PathBuf) {
-        if new_dir.is_dir() && new_dir.parent().is_some() {
+        let new_dir = match utils::normalize_path(&new_dir) {
+            Ok(dir) => dir,
+            Err(e) => {
+                eprintln!("Ignoring the extraction dir: {e}");
+                return;
+            }
+        };
+        // The dir might not exist yet, the installer asks before creating it
+        if !new_dir.is_file() && new_dir.parent().is_some() {
+            let is_valid = utils::is_valid_ddlc_dir(&new_dir);
+            log::debug!("{} is a valid DDLC dir: {is_valid}", new_dir.display());
             let mut app_state = self.state.lock().unwrap();
             app_state.set_extraction_dir(new_dir);
+            app_state.set_ddlc_dir_valid(Some(is_valid));
             self.path_txt_buf.set_text(app_state.get_extraction_dir_str());
         }
     }
+
+    /// Checks the extraction dir suits the current mode, tells the user what's wrong,
+    /// returns true if we can continue
+    fn check_selected_dir(&mut self) -> bool {
+        let app_state = self.state.lock().unwrap();
+        let is_uninstalling = app_state.get_uninstall_flag();
+        let is_verifying = app_state.get_verify_flag();
+        let is_spr_only = app_state.get_spr_only_flag();
+        let is_copying = app_state.get_copy_source().is_some();
+        drop(app_state);
+
+        // The copy goes into a new folder, it doesn't have to be a DDLC one
+        if is_copying {
+            if !utils::is_usable_copy_target(self.state.lock().unwrap().get_extraction_dir()) {
+                utils::run_alert_dlg(strings::get_strings().copy_target_not_empty_dlg_msg);
+                return false;
+            }
+        }
+        // Make sure the user really wants to install into a wrong folder
+        else if !is_uninstalling && !is_verifying && !self.confirm_extraction_dir() {
+            return false;
+        }
+        // The spritepacks need MAS to be there already
+        if is_spr_only {
+            let has_mas = utils::detect_mas_install(self.state.lock().unwrap().get_extraction_dir()).is_some();
+            if !has_mas {
+                utils::run_alert_dlg(strings::get_strings().spr_only_no_mas_dlg_msg);
+                return false;
+            }
+        }
+        return true;
+    }
+
+    /// Asks the user to confirm the extraction dir if it doesn't look like a DDLC dir,
+    /// returns true if we can continue, the choice is remembered for this dir
+    fn confirm_extraction_dir(&mut self) -> bool {
+        let mut app_state = self.state.lock().unwrap();
+        if app_state.get_invalid_dir_confirmed() {
+            return true;
+        }
+        let is_valid = match app_state.get_ddlc_dir_valid() {
+            Some(is_valid) => is_valid,
+            None => {
+                let is_valid = utils::is_valid_ddlc_dir(app_state.get_extraction_dir());
+                app_state.set_ddlc_dir_valid(Some(is_valid));
+                is_valid
+            }
+        };
+        if is_valid {
+            return true;
+        }
+        // Don't hold the lock while the dialog is shown
+        drop(app_state);
+        let confirmed = utils::run_choice_dlg(
+            strings::get_strings().non_ddlc_dir_confirm_dlg_msg,
+            strings::get_strings().but_continue_anyway_label,
+            strings::get_strings().but_back_label
+        );
+        self.state.lock().unwrap().set_invalid_dir_confirmed(confirmed);
+        return confirmed;
+    }
 }
 
 impl Default for InstallerApp {
     fn default() -> Self {
-        return Self::new();
+        return Self::new(&config::load_config());
     }
 }
 
@@ -348,3 +1871,7 @@ impl Drop for InstallerApp {
         self.inner.quit();
     }
 }
+
+
+#[cfg(test)]
+mod tests;
@@ -1,11 +1,19 @@
 /// The module that implements AppState
 
 use std::{
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{
         Arc,
         Mutex
     },
+    time::{Duration, Instant}
+};
+
+
+use crate::{
+    app::{strings::Language, styles::Theme},
+    config::{Config, ProxyConfig, WindowGeometry},
+    utils::{DryRunReport, ReleaseInfo, ReleaseNotes, VerifyReport}
 };
 
 
@@ -16,9 +24,65 @@ pub type ThreadSafeState = Arc<Mutex<AppState>>;
 #[derive(Debug)]
 pub struct AppState {
     extraction_dir: PathBuf,
+    ddlc_dir_valid: Option<bool>,
+    invalid_dir_confirmed: bool,
+    populated_dir_confirmed: bool,
+    create_dir_confirmed: bool,
+    existing_entries: usize,
     abort_flag: bool,
+    worker_running_flag: bool,
+    last_progress: Instant,
+    stalled_flag: bool,
+    paused_flag: bool,
     deluxe_ver_flag: bool,
-    install_spr_flag: bool
+    install_spr_flag: bool,
+    resume_flag: bool,
+    start_over_flag: bool,
+    stale_files: Vec<String>,
+    remove_stale_files: Option<bool>,
+    clean_install_flag: bool,
+    backup_flag: bool,
+    shortcut_flag: bool,
+    keep_archives_flag: bool,
+    keep_archives_dir: Option<PathBuf>,
+    kept_archives: Vec<PathBuf>,
+    dry_run_flag: bool,
+    dry_run_report: Option<DryRunReport>,
+    extracting_file: Option<String>,
+    installing_tag: Option<String>,
+    prerelease_flag: bool,
+    uninstall_flag: bool,
+    spr_only_flag: bool,
+    verify_flag: bool,
+    verify_report: Option<VerifyReport>,
+    local_archive: Option<PathBuf>,
+    copy_source: Option<PathBuf>,
+    release_tag: Option<String>,
+    releases: Option<Vec<ReleaseInfo>>,
+    release_notes: Option<ReleaseNotes>,
+    proxy: ProxyConfig,
+    theme: Theme,
+    language: Option<Language>,
+    chunk_size_kb: Option<u64>,
+    speed_limit_mb: Option<f64>,
+    thorough_verification: bool,
+    connect_timeout_secs: Option<u64>,
+    read_timeout_secs: Option<u64>,
+    stall_timeout_secs: Option<u64>,
+    cache_downloads: bool,
+    cache_size_mb: Option<u64>,
+    parallel_downloads: bool,
+    temp_dir: Option<PathBuf>,
+    github_org: Option<String>,
+    github_repo: Option<String>,
+    repo: String,
+    spritepacks_dir: Option<PathBuf>,
+    spr_subdir: PathBuf,
+    spr_bundles: Option<Vec<String>>,
+    spr_subfolders_flag: bool,
+    window_geometry: Option<WindowGeometry>,
+    compact_mode: bool,
+    max_retries: u32
 }
 
 impl AppState {
@@ -26,9 +90,65 @@ impl AppState {
     pub fn new( extraction_dir: PathBuf, abort_flag: bool, deluxe_ver_flag: bool, install_spr_flag: bool) -> Self {
         return Self {
             extraction_dir,
+            ddlc_dir_valid: None,
+            invalid_dir_confirmed: false,
+            populated_dir_confirmed: false,
+            create_dir_confirmed: false,
+            existing_entries: 0,
             abort_flag,
+            worker_running_flag: false,
+            last_progress: Instant::now(),
+            stalled_flag: false,
+            paused_flag: false,
             deluxe_ver_flag,
-            install_spr_flag
+            install_spr_flag,
+            resume_flag: false,
+            start_over_flag: false,
+            stale_files: Vec::new(),
+            remove_stale_files: None,
+            clean_install_flag: false,
+            backup_flag: false,
+            shortcut_flag: false,
+            keep_archives_flag: false,
+            keep_archives_dir: None,
+            kept_archives: Vec::new(),
+            dry_run_flag: false,
+            dry_run_report: None,
+            extracting_file: None,
+            installing_tag: None,
+            prerelease_flag: false,
+            uninstall_flag: false,
+            spr_only_flag: false,
+            verify_flag: false,
+            verify_report: None,
+            local_archive: None,
+            copy_source: None,
+            release_tag: None,
+            releases: None,
+            release_notes: None,
+            proxy: ProxyConfig::default(),
+            theme: Theme::default(),
+            language: None,
+            chunk_size_kb: None,
+            speed_limit_mb: None,
+            thorough_verification: false,
+            connect_timeout_secs: None,
+            read_timeout_secs: None,
+            stall_timeout_secs: None,
+            cache_downloads: true,
+            cache_size_mb: None,
+            parallel_downloads: false,
+            temp_dir: None,
+            github_org: None,
+            github_repo: None,
+            repo: crate::utils::resolve_repo(None, None),
+            spritepacks_dir: None,
+            spr_subdir: crate::utils::resolve_spr_subdir(None),
+            spr_bundles: None,
+            spr_subfolders_flag: false,
+            window_geometry: None,
+            compact_mode: false,
+            max_retries: crate::DEF_MAX_RETRIES
         };
     }
 
@@ -42,13 +162,57 @@ impl AppState {
         self.abort_flag = value;
     }
 
+    /// Returns the flag of the installer thread running
+    pub fn get_worker_running_flag(&self) -> bool {
+        return self.worker_running_flag;
+    }
+
+    /// Sets the flag of the installer thread running
+    pub fn set_worker_running_flag(&mut self, value: bool) {
+        self.worker_running_flag = value;
+    }
+
+    /// Returns when the installer last reported anything
+    pub fn get_last_progress(&self) -> Instant {
+        return self.last_progress;
+    }
+
+    /// Sets when the installer last reported anything
+    pub fn set_last_progress(&mut self, value: Instant) {
+        self.last_progress = value;
+    }
+
+    /// Returns the flag of the install being aborted by the watchdog
+    pub fn get_stalled_flag(&self) -> bool {
+        return self.stalled_flag;
+    }
+
+    /// Sets the flag of the install being aborted by the watchdog
+    pub fn set_stalled_flag(&mut self, value: bool) {
+        self.stalled_flag = value;
+    }
+
+    /// Returns the paused flag
+    pub fn get_paused_flag(&self) -> bool {
+        return self.paused_flag;
+    }
+
+    /// Sets the paused flag
+    pub fn set_paused_flag(&mut self, value: bool) {
+        self.paused_flag = value;
+    }
+
+    /// Inverts the paused flag
+    pub fn invert_paused_flag(&mut self) {
+        self.paused_flag = !self.paused_flag;
+    }
+
     /// Returns the dlx version flag
     pub fn get_deluxe_ver_flag(&self) -> bool {
         return self.deluxe_ver_flag;
     }
 
     /// Sets the dlx version flag
-    #[allow(dead_code)]
     pub fn set_deluxe_ver_flag(&mut self, value: bool) {
         self.deluxe_ver_flag = value;
     }
@@ -64,7 +228,6 @@ impl AppState {
     }
 
     /// Sets the install spritepacks flag
-    #[allow(dead_code)]
     pub fn set_install_spr_flag(&mut self, value: bool) {
         self.install_spr_flag = value;
     }
@@ -74,6 +237,427 @@ impl AppState {
         self.install_spr_flag = !self.install_spr_flag;
     }
 
+    /// Returns the backup flag
+    pub fn get_backup_flag(&self) -> bool {
+        return self.backup_flag;
+    }
+
+    /// Sets the backup flag
+    #[allow(dead_code)]
+    pub fn set_backup_flag(&mut self, value: bool) {
+        self.backup_flag = value;
+    }
+
+    /// Inverts the backup flag
+    pub fn invert_backup_flag(&mut self) {
+        self.backup_flag = !self.backup_flag;
+    }
+
+    /// Returns the shortcut flag
+    pub fn get_shortcut_flag(&self) -> bool {
+        return self.shortcut_flag;
+    }
+
+    /// Sets the shortcut flag
+    pub fn set_shortcut_flag(&mut self, value: bool) {
+        self.shortcut_flag = value;
+    }
+
+    /// Inverts the shortcut flag
+    pub fn invert_shortcut_flag(&mut self) {
+        self.shortcut_flag = !self.shortcut_flag;
+    }
+
+    /// Returns the flag of keeping the downloaded archives
+    pub fn get_keep_archives_flag(&self) -> bool {
+        return self.keep_archives_flag;
+    }
+
+    /// Sets the flag of keeping the downloaded archives
+    pub fn set_keep_archives_flag(&mut self, value: bool) {
+        self.keep_archives_flag = value;
+    }
+
+    /// Inverts the flag of keeping the downloaded archives
+    pub fn invert_keep_archives_flag(&mut self) {
+        self.keep_archives_flag = !self.keep_archives_flag;
+    }
+
+    /// Returns the dir to keep the archives in, None means the install dir
+    pub fn get_keep_archives_dir(&self) -> Option<&Path> {
+        return self.keep_archives_dir.as_deref();
+    }
+
+    /// Returns the paths of the archives kept after the last install
+    pub fn get_kept_archives(&self) -> &[PathBuf] {
+        return &self.kept_archives;
+    }
+
+    /// Sets the paths of the archives kept after the last install
+    pub fn set_kept_archives(&mut self, value: Vec<PathBuf>) {
+        self.kept_archives = value;
+    }
+
+    /// Returns the dry run flag
+    pub fn get_dry_run_flag(&self) -> bool {
+        return self.dry_run_flag;
+    }
+
+    /// Sets the dry run flag
+    pub fn set_dry_run_flag(&mut self, value: bool) {
+        self.dry_run_flag = value;
+    }
+
+    /// Inverts the dry run flag
+    pub fn invert_dry_run_flag(&mut self) {
+        self.dry_run_flag = !self.dry_run_flag;
+    }
+
+    /// Returns the report of the last dry run, None if there's none to proceed with
+    pub fn get_dry_run_report(&self) -> Option<&DryRunReport> {
+        return self.dry_run_report.as_ref();
+    }
+
+    /// Sets the report of the last dry run
+    pub fn set_dry_run_report(&mut self, value: Option<DryRunReport>) {
+        self.dry_run_report = value;
+    }
+
+    /// Returns the name of the archive entry being extracted
+    pub fn get_extracting_file(&self) -> Option<&str> {
+        return self.extracting_file.as_deref();
+    }
+
+    /// Sets the name of the archive entry being extracted
+    pub fn set_extracting_file(&mut self, value: Option<String>) {
+        self.extracting_file = value;
+    }
+
+    /// Returns the tag of the release being installed, None until the installer resolves it
+    pub fn get_installing_tag(&self) -> Option<&str> {
+        return self.installing_tag.as_deref();
+    }
+
+    /// Sets the tag of the release being installed
+    pub fn set_installing_tag(&mut self, value: Option<String>) {
+        self.installing_tag = value;
+    }
+
+    /// Returns the pre-release flag
+    pub fn get_prerelease_flag(&self) -> bool {
+        return self.prerelease_flag;
+    }
+
+    /// Sets the pre-release flag
+    #[allow(dead_code)]
+    pub fn set_prerelease_flag(&mut self, value: bool) {
+        self.prerelease_flag = value;
+    }
+
+    /// Inverts the pre-release flag
+    pub fn invert_prerelease_flag(&mut self) {
+        self.prerelease_flag = !self.prerelease_flag;
+    }
+
+    /// Returns the uninstall flag
+    pub fn get_uninstall_flag(&self) -> bool {
+        return self.uninstall_flag;
+    }
+
+    /// Sets the uninstall flag
+    pub fn set_uninstall_flag(&mut self, value: bool) {
+        self.uninstall_flag = value;
+    }
+
+    /// Returns the spritepacks-only flag
+    pub fn get_spr_only_flag(&self) -> bool {
+        return self.spr_only_flag;
+    }
+
+    /// Sets the spritepacks-only flag
+    pub fn set_spr_only_flag(&mut self, value: bool) {
+        self.spr_only_flag = value;
+    }
+
+    /// Returns the verify flag
+    pub fn get_verify_flag(&self) -> bool {
+        return self.verify_flag;
+    }
+
+    /// Sets the verify flag
+    pub fn set_verify_flag(&mut self, value: bool) {
+        self.verify_flag = value;
+    }
+
+    /// Returns the report of the last verification, None if there's none to repair from
+    pub fn get_verify_report(&self) -> Option<&VerifyReport> {
+        return self.verify_report.as_ref();
+    }
+
+    /// Sets the report of the last verification
+    pub fn set_verify_report(&mut self, value: Option<VerifyReport>) {
+        self.verify_report = value;
+    }
+
+    /// Returns the resume flag
+    pub fn get_resume_flag(&self) -> bool {
+        return self.resume_flag;
+    }
+
+    /// Sets the resume flag
+    pub fn set_resume_flag(&mut self, value: bool) {
+        self.resume_flag = value;
+    }
+
+    /// Returns the start over flag, if it's set the cached downloads aren't used
+    pub fn get_start_over_flag(&self) -> bool {
+        return self.start_over_flag;
+    }
+
+    /// Sets the start over flag
+    pub fn set_start_over_flag(&mut self, value: bool) {
+        self.start_over_flag = value;
+    }
+
+    /// Returns the files the previous version left behind, relative to the DDLC dir
+    pub fn get_stale_files(&self) -> &[String] {
+        return &self.stale_files;
+    }
+
+    /// Sets the files the previous version left behind
+    pub fn set_stale_files(&mut self, value: Vec<String>) {
+        self.stale_files = value;
+    }
+
+    /// Returns whether the user wants the stale files removed,
+    /// None if they haven't been asked yet
+    pub fn get_remove_stale_files(&self) -> Option<bool> {
+        return self.remove_stale_files;
+    }
+
+    /// Sets whether the user wants the stale files removed
+    pub fn set_remove_stale_files(&mut self, value: Option<bool>) {
+        self.remove_stale_files = value;
+    }
+
+    /// Returns the clean install flag
+    pub fn get_clean_install_flag(&self) -> bool {
+        return self.clean_install_flag;
+    }
+
+    /// Sets the clean install flag
+    pub fn set_clean_install_flag(&mut self, value: bool) {
+        self.clean_install_flag = value;
+    }
+
+    /// Returns the tag of the release to install, None means the latest
+    pub fn get_release_tag(&self) -> Option<&str> {
+        return self.release_tag.as_deref();
+    }
+
+    /// Sets the tag of the release to install, None means the latest
+    pub fn set_release_tag(&mut self, value: Option<String>) {
+        self.release_tag = value;
+    }
+
+    /// Returns the cached list of releases, None if it hasn't been fetched yet
+    pub fn get_releases(&self) -> Option<&Vec<ReleaseInfo>> {
+        return self.releases.as_ref();
+    }
+
+    /// Sets the cached list of releases
+    pub fn set_releases(&mut self, value: Option<Vec<ReleaseInfo>>) {
+        self.releases = value;
+    }
+
+    /// Returns the cached release notes, None if they haven't been fetched yet
+    pub fn get_release_notes(&self) -> Option<&ReleaseNotes> {
+        return self.release_notes.as_ref();
+    }
+
+    /// Sets the cached release notes
+    pub fn set_release_notes(&mut self, value: Option<ReleaseNotes>) {
+        self.release_notes = value;
+    }
+
+    /// Returns the local archive to install from, None means downloading from GitHub
+    pub fn get_local_archive(&self) -> Option<&PathBuf> {
+        return self.local_archive.as_ref();
+    }
+
+    /// Sets the local archive to install from
+    pub fn set_local_archive(&mut self, value: Option<PathBuf>) {
+        self.local_archive = value;
+    }
+
+    /// Returns the DDLC dir that gets copied into the extraction dir before the install,
+    /// None means installing into the extraction dir as it is
+    pub fn get_copy_source(&self) -> Option<&Path> {
+        return self.copy_source.as_deref();
+    }
+
+    /// Sets the DDLC dir to copy
+    pub fn set_copy_source(&mut self, value: Option<PathBuf>) {
+        self.copy_source = value;
+    }
+
+    /// Returns the proxy settings
+    pub fn get_proxy(&self) -> &ProxyConfig {
+        return &self.proxy;
+    }
+
+    /// Sets the proxy url, None means using the env vars
+    pub fn set_proxy_url(&mut self, value: Option<String>) {
+        self.proxy.url = value;
+    }
+
+    /// Returns the color theme
+    pub fn get_theme(&self) -> Theme {
+        return self.theme;
+    }
+
+    /// Switches between the light and the dark themes
+    pub fn invert_theme(&mut self) {
+        self.theme = match self.theme {
+            Theme::Light => Theme::Dark,
+            Theme::Dark => Theme::Light
+        };
+    }
+
+    /// Returns the language picked by the user, None means detecting it
+    pub fn get_language(&self) -> Option<Language> {
+        return self.language;
+    }
+
+    /// Sets the language picked by the user
+    pub fn set_language(&mut self, value: Option<Language>) {
+        self.language = value;
+    }
+
+    /// Returns the download chunk size in KiB, None means auto-tuning
+    pub fn get_chunk_size_kb(&self) -> Option<u64> {
+        return self.chunk_size_kb;
+    }
+
+    /// Returns the download speed limit in MB/s, None means unlimited
+    pub fn get_speed_limit_mb(&self) -> Option<f64> {
+        return self.speed_limit_mb;
+    }
+
+    /// Sets the download speed limit in MB/s
+    pub fn set_speed_limit_mb(&mut self, value: Option<f64>) {
+        self.speed_limit_mb = value;
+    }
+
+    /// Returns true if the extracted files should be checked against the archive
+    pub fn get_thorough_verification_flag(&self) -> bool {
+        return self.thorough_verification;
+    }
+
+    /// Returns true if the user wants to skip the wizard
+    pub fn get_compact_mode_flag(&self) -> bool {
+        return self.compact_mode;
+    }
+
+    /// Returns true if spritepacks should be downloaded while MAS is being extracted
+    pub fn get_parallel_downloads_flag(&self) -> bool {
+        return self.parallel_downloads;
+    }
+
+    /// Returns the dir for partial downloads picked by the user, None means the default one, see utils::get_cache_dir
+    pub fn get_temp_dir(&self) -> Option<&Path> {
+        return self.temp_dir.as_deref();
+    }
+
+    /// Sets the dir for partial downloads
+    pub fn set_temp_dir(&mut self, value: Option<PathBuf>) {
+        self.temp_dir = value;
+    }
+
+    /// Returns the GitHub repo we install from, e.g. "Monika-After-Story/MonikaModDev"
+    pub fn get_repo(&self) -> &str {
+        return &self.repo;
+    }
+
+    /// Returns the names of the spritepack bundles the user chose,
+    /// None if they haven't, then the combined bundle is used
+    pub fn get_spr_bundles(&self) -> Option<&[String]> {
+        return self.spr_bundles.as_deref();
+    }
+
+    /// Sets the names of the spritepack bundles to install
+    pub fn set_spr_bundles(&mut self, value: Option<Vec<String>>) {
+        self.spr_bundles = value;
+    }
+
+    /// Returns whether each spritepack bundle goes into its own subfolder
+    pub fn get_spr_subfolders_flag(&self) -> bool {
+        return self.spr_subfolders_flag;
+    }
+
+    /// Sets whether each spritepack bundle goes into its own subfolder
+    pub fn set_spr_subfolders_flag(&mut self, value: bool) {
+        self.spr_subfolders_flag = value;
+    }
+
+    /// Returns the dir for the spritepacks, relative to the DDLC dir
+    pub fn get_spr_subdir(&self) -> &Path {
+        return &self.spr_subdir;
+    }
+
+    /// Returns where the main window was last time
+    pub fn get_window_geometry(&self) -> Option<&WindowGeometry> {
+        return self.window_geometry.as_ref();
+    }
+
+    /// Sets where the main window is, so it opens there next time
+    pub fn set_window_geometry(&mut self, value: Option<WindowGeometry>) {
+        self.window_geometry = value;
+    }
+
+    /// Returns how long we wait for a connection
+    pub fn get_connect_timeout(&self) -> Duration {
+        return self.connect_timeout_secs
+            .filter(|&secs| secs > 0)
+            .map_or(crate::DEF_CONNECT_TIMEOUT, Duration::from_secs);
+    }
+
+    /// Returns how long we wait for data before giving up on a request
+    pub fn get_read_timeout(&self) -> Duration {
+        return self.read_timeout_secs
+            .filter(|&secs| secs > 0)
+            .map_or(crate::DEF_READ_TIMEOUT, Duration::from_secs);
+    }
+
+    /// Returns how long the install can go without progress, None if we don't check it
+    pub fn get_stall_timeout(&self) -> Option<Duration> {
+        return match self.stall_timeout_secs {
+            Some(0) => None,
+            Some(secs) => Some(Duration::from_secs(secs)),
+            None => Some(crate::DEF_STALL_TIMEOUT)
+        };
+    }
+
+    /// Returns true if the downloads should be kept for the next install
+    pub fn get_cache_downloads_flag(&self) -> bool {
+        return self.cache_downloads;
+    }
+
+    /// Returns the max size of the download cache in bytes
+    pub fn get_cache_size_limit(&self) -> u64 {
+        return self.cache_size_mb.unwrap_or(crate::DEF_CACHE_SIZE_MB) * 1024 * 1024;
+    }
+
+    /// Returns the max number of retries for failed requests
+    pub fn get_max_retries(&self) -> u32 {
+        return self.max_retries;
+    }
+
+    /// Sets the max number of retries for failed requests
+    pub fn set_max_retries(&mut self, value: u32) {
+        self.max_retries = value;
+    }
+
     /// Returns the extraction directory
     pub fn get_extraction_dir(&self) -> &PathBuf {
         return &self.extraction_dir;
@@ -85,9 +669,146 @@ impl AppState {
     }
 
     /// Sets the extraction directory
+    /// NOTE: resets the validity of the dir if it's a different one
     pub fn set_extraction_dir(&mut self, new_path: PathBuf) {
+        if new_path != self.extraction_dir {
+            self.ddlc_dir_valid = None;
+            self.invalid_dir_confirmed = false;
+            self.populated_dir_confirmed = false;
+            self.create_dir_confirmed = false;
+        }
         self.extraction_dir = new_path;
     }
+
+    /// Returns whether the extraction dir looks like a DDLC dir,
+    /// None if it hasn't been checked
+    pub fn get_ddlc_dir_valid(&self) -> Option<bool> {
+        return self.ddlc_dir_valid;
+    }
+
+    /// Sets whether the extraction dir looks like a DDLC dir
+    pub fn set_ddlc_dir_valid(&mut self, value: Option<bool>) {
+        self.ddlc_dir_valid = value;
+    }
+
+    /// Returns true if the user chose to continue with the invalid extraction dir
+    pub fn get_invalid_dir_confirmed(&self) -> bool {
+        return self.invalid_dir_confirmed;
+    }
+
+    /// Sets whether the user chose to continue with the invalid extraction dir
+    pub fn set_invalid_dir_confirmed(&mut self, value: bool) {
+        self.invalid_dir_confirmed = value;
+    }
+
+    /// Returns whether the user chose to install into the non-empty non-DDLC dir
+    pub fn get_populated_dir_confirmed(&self) -> bool {
+        return self.populated_dir_confirmed;
+    }
+
+    /// Sets whether the user chose to install into the non-empty non-DDLC dir
+    pub fn set_populated_dir_confirmed(&mut self, value: bool) {
+        self.populated_dir_confirmed = value;
+    }
+
+    /// Returns whether the user agreed to create the missing extraction dir
+    pub fn get_create_dir_confirmed(&self) -> bool {
+        return self.create_dir_confirmed;
+    }
+
+    /// Sets whether the user agreed to create the missing extraction dir
+    pub fn set_create_dir_confirmed(&mut self, value: bool) {
+        self.create_dir_confirmed = value;
+    }
+
+    /// Returns the number of files and folders found in the extraction dir
+    pub fn get_existing_entries(&self) -> usize {
+        return self.existing_entries;
+    }
+
+    /// Sets the number of files and folders found in the extraction dir
+    pub fn set_existing_entries(&mut self, value: usize) {
+        self.existing_entries = value;
+    }
+}
+
+impl AppState {
+    /// Creates a new AppState using the user preferences from the config
+    pub fn from_config(config: &Config) -> Self {
+        let mut state = Self::default();
+        let config_dir = config.extraction_dir.as_deref().and_then(|dir| crate::utils::normalize_path(dir).ok());
+        if let Some(dir) = config_dir.filter(|dir| dir.is_dir()) {
+            state.extraction_dir = dir;
+        }
+        if let Some(value) = config.deluxe_version {
+            state.deluxe_ver_flag = value;
+        }
+        if let Some(value) = config.install_spritepacks {
+            state.install_spr_flag = value;
+        }
+        if let Some(value) = config.theme {
+            state.theme = value;
+        }
+        state.language = config.language;
+        state.chunk_size_kb = config.chunk_size_kb;
+        state.speed_limit_mb = config.speed_limit_mb;
+        state.thorough_verification = config.thorough_verification.unwrap_or(false);
+        state.connect_timeout_secs = config.connect_timeout_secs;
+        state.read_timeout_secs = config.read_timeout_secs;
+        state.stall_timeout_secs = config.stall_timeout_secs;
+        if let Some(value) = config.max_retries {
+            state.max_retries = value;
+        }
+        if let Some(value) = config.cache_downloads {
+            state.cache_downloads = value;
+        }
+        state.cache_size_mb = config.cache_size_mb;
+        state.parallel_downloads = config.parallel_downloads.unwrap_or(false);
+        state.temp_dir = config.temp_dir.clone();
+        state.keep_archives_dir = config.keep_archives_dir.clone();
+        state.github_org = config.github_org.clone();
+        state.github_repo = config.github_repo.clone();
+        state.repo = crate::utils::resolve_repo(config.github_org.as_deref(), config.github_repo.as_deref());
+        state.spr_bundles = config.spritepack_bundles.clone();
+        state.spr_subfolders_flag = config.spritepack_subfolders.unwrap_or(false);
+        state.spritepacks_dir = config.spritepacks_dir.clone();
+        state.spr_subdir = crate::utils::resolve_spr_subdir(config.spritepacks_dir.as_deref());
+        state.window_geometry = config.window_geometry;
+        state.compact_mode = config.compact_mode.unwrap_or(false);
+        state.proxy = config.proxy.clone();
+        return state;
+    }
+
+    /// Returns a config with the user preferences from this state
+    pub fn to_config(&self) -> Config {
+        return Config {
+            extraction_dir: Some(self.extraction_dir.clone()),
+            deluxe_version: Some(self.deluxe_ver_flag),
+            install_spritepacks: Some(self.install_spr_flag),
+            theme: Some(self.theme),
+            language: self.language,
+            chunk_size_kb: self.chunk_size_kb,
+            speed_limit_mb: self.speed_limit_mb,
+            thorough_verification: Some(self.thorough_verification).filter(|&value| value),
+            connect_timeout_secs: self.connect_timeout_secs,
+            read_timeout_secs: self.read_timeout_secs,
+            stall_timeout_secs: self.stall_timeout_secs,
+            max_retries: Some(self.max_retries).filter(|&value| value != crate::DEF_MAX_RETRIES),
+            cache_downloads: Some(self.cache_downloads),
+            cache_size_mb: self.cache_size_mb,
+            parallel_downloads: Some(self.parallel_downloads).filter(|&value| value),
+            temp_dir: self.temp_dir.clone(),
+            keep_archives_dir: self.keep_archives_dir.clone(),
+            github_org: self.github_org.clone(),
+            github_repo: self.github_repo.clone(),
+            spritepacks_dir: self.spritepacks_dir.clone(),
+            spritepack_bundles: self.spr_bundles.clone(),
+            spritepack_subfolders: Some(self.spr_subfolders_flag).filter(|&value| value),
+            window_geometry: self.window_geometry,
+            compact_mode: Some(self.compact_mode).filter(|&value| value),
+            proxy: self.proxy.clone()
+        };
+    }
 }
 
 impl Default for AppState {
@@ -102,7 +823,7 @@ impl Default for AppState {
 }
 
 
-/// Builds an AppState and wraps it into a Mutex inside an Arc
-pub fn build_thread_safe_state() -> ThreadSafeState {
-    return Arc::new(Mutex::new(AppState::default()));
+/// Builds an AppState from the config and wraps it into a Mutex inside an Arc
+pub fn build_thread_safe_state(config: &Config) -> ThreadSafeState {
+    return Arc::new(Mutex::new(AppState::from_config(config)));
 }
@@ -1,8 +1,9 @@
 /// The module with styles for our app
 
-use std::sync::Mutex;
+use std::{sync::Mutex, time::Duration};
 
 use fltk::{
+    app,
     enums::{
         Color,
         Font
@@ -10,19 +11,21 @@ use fltk::{
     image::PngImage
 };
 
-
-// App title
-pub const WIN_TITLE: &str = "Monika After Story Installer";
-// Err window title
-pub const ALERT_WIN_TITLE: &str = "Error!";
-// Msg window title
-pub const MSG_WIN_TITLE: &str = "Attention!";
+use serde::{
+    Deserialize,
+    Serialize
+};
 
 
 // Window consts
 pub const WIN_WIDTH: i32 = 600;
 pub const WIN_HEIGHT: i32 = 500;
 
+// The window can be resized, but the text doesn't scale,
+// so it can't get much smaller than the default
+pub const MIN_WIN_WIDTH: i32 = WIN_WIDTH*4/5;
+pub const MIN_WIN_HEIGHT: i32 = WIN_HEIGHT*4/5;
+
 pub const WIN_PADDING: i32 = 4;
 
 pub const INNER_WIN_WIDTH: i32 = WIN_WIDTH - 2*WIN_PADDING;
@@ -40,6 +43,57 @@ pub const MSG_WIN_HEIGHT: i32 = ALERT_WIN_HEIGHT;
 pub const INNER_MSG_WIN_WIDTH: i32 = MSG_WIN_WIDTH - 2*WIN_PADDING;
 pub const INNER_MSG_WIN_HEIGHT: i32 = MSG_WIN_HEIGHT - 2*WIN_PADDING;
 
+pub const CHOICE_WIN_WIDTH: i32 = MSG_WIN_WIDTH;
+pub const CHOICE_WIN_HEIGHT: i32 = MSG_WIN_HEIGHT;
+
+pub const INNER_CHOICE_WIN_WIDTH: i32 = CHOICE_WIN_WIDTH - 2*WIN_PADDING;
+pub const INNER_CHOICE_WIN_HEIGHT: i32 = CHOICE_WIN_HEIGHT - 2*WIN_PADDING;
+
+pub const PROXY_WIN_WIDTH: i32 = MSG_WIN_WIDTH;
+pub const PROXY_WIN_HEIGHT: i32 = MSG_WIN_HEIGHT;
+
+pub const INNER_PROXY_WIN_WIDTH: i32 = PROXY_WIN_WIDTH - 2*WIN_PADDING;
+pub const INNER_PROXY_WIN_HEIGHT: i32 = PROXY_WIN_HEIGHT - 2*WIN_PADDING;
+
+pub const ABOUT_WIN_WIDTH: i32 = MSG_WIN_WIDTH;
+pub const ABOUT_WIN_HEIGHT: i32 = 400;
+
+pub const INNER_ABOUT_WIN_WIDTH: i32 = ABOUT_WIN_WIDTH - 2*WIN_PADDING;
+pub const INNER_ABOUT_WIN_HEIGHT: i32 = ABOUT_WIN_HEIGHT - 2*WIN_PADDING;
+
+pub const SPR_BUNDLES_WIN_WIDTH: i32 = MSG_WIN_WIDTH;
+pub const INNER_SPR_BUNDLES_WIN_WIDTH: i32 = SPR_BUNDLES_WIN_WIDTH - 2*WIN_PADDING;
+// The window grows with the number of bundles, one check button for each
+pub const SPR_BUNDLES_FRAME_HEIGHT: i32 = 50;
+pub const SPR_BUNDLES_CHECK_HEIGHT: i32 = BUT_HEIGHT;
+
+pub const LICENSE_VIEWER_WIN_WIDTH: i32 = ABOUT_WIN_WIDTH;
+pub const LICENSE_VIEWER_WIN_HEIGHT: i32 = ABOUT_WIN_HEIGHT;
+
+pub const INNER_LICENSE_VIEWER_WIN_WIDTH: i32 = LICENSE_VIEWER_WIN_WIDTH - 2*WIN_PADDING;
+pub const INNER_LICENSE_VIEWER_WIN_HEIGHT: i32 = LICENSE_VIEWER_WIN_HEIGHT - 2*WIN_PADDING;
+pub const LICENSE_VIEWER_NOTICE_HEIGHT: i32 = 40;
+
+pub const ABOUT_INFO_FRAME_HEIGHT: i32 = 80;
+pub const ABOUT_LINK_HEIGHT: i32 = 25;
+pub const ABOUT_TXT_SIZE: i32 = LABEL_SIZE_SMALL;
+
+pub const DRY_RUN_WIN_WIDTH: i32 = MSG_WIN_WIDTH;
+pub const DRY_RUN_WIN_HEIGHT: i32 = 400;
+
+pub const INNER_DRY_RUN_WIN_WIDTH: i32 = DRY_RUN_WIN_WIDTH - 2*WIN_PADDING;
+pub const INNER_DRY_RUN_WIN_HEIGHT: i32 = DRY_RUN_WIN_HEIGHT - 2*WIN_PADDING;
+
+pub const DRY_RUN_INFO_FRAME_HEIGHT: i32 = 40;
+pub const DRY_RUN_TXT_SIZE: i32 = LABEL_SIZE_SMALL;
+
+// The compact window fits the title, the bar and the cancel button
+pub const COMPACT_WIN_WIDTH: i32 = WIN_WIDTH;
+pub const COMPACT_WIN_HEIGHT: i32 = INNER_COMPACT_WIN_HEIGHT + 2*WIN_PADDING;
+
+pub const INNER_COMPACT_WIN_WIDTH: i32 = COMPACT_WIN_WIDTH - 2*WIN_PADDING;
+pub const INNER_COMPACT_WIN_HEIGHT: i32 = COMPACT_PB_YPOS + PB_HEIGHT + INNER_WIN_CONTENT_YPADDING + BUT_HEIGHT + BUT_PACK_YPADDING;
+
 pub const INNER_WIN_CONTENT_XPADDING: i32 = 20;
 pub const INNER_WIN_CONTENT_YPADDING: i32 = INNER_WIN_CONTENT_XPADDING;
 
@@ -54,9 +108,29 @@ pub const BUT_MUTE_HEIGHT: i32 = BUT_MUTE_WIDTH;
 pub const BUT_DLX_VER_CHECK_WIDTH: i32 = BUT_WIDTH + 225;
 pub const BUT_DLX_VER_CHECK_HEIGHT: i32 = BUT_HEIGHT;
 
-pub const BUT_INSTALL_SPR_CHECK_WIDTH: i32 = BUT_WIDTH + 380;
+// The bundles button goes next to it
+pub const BUT_INSTALL_SPR_CHECK_WIDTH: i32 = BUT_WIDTH + 280;
 pub const BUT_INSTALL_SPR_CHECK_HEIGHT: i32 = BUT_HEIGHT;
 
+pub const BUT_BACKUP_CHECK_WIDTH: i32 = BUT_WIDTH + 330;
+pub const BUT_BACKUP_CHECK_HEIGHT: i32 = BUT_HEIGHT;
+
+pub const BUT_SHORTCUT_CHECK_WIDTH: i32 = BUT_WIDTH + 200;
+pub const BUT_SHORTCUT_CHECK_HEIGHT: i32 = BUT_HEIGHT;
+
+// Goes next to the shortcut check button
+pub const BUT_KEEP_ARCHIVES_CHECK_WIDTH: i32 = INNER_WIN_WIDTH - 2*INNER_WIN_CONTENT_XPADDING - BUT_SHORTCUT_CHECK_WIDTH - BUT_SPACING;
+pub const BUT_KEEP_ARCHIVES_CHECK_HEIGHT: i32 = BUT_HEIGHT;
+
+pub const BUT_DRY_RUN_CHECK_WIDTH: i32 = BUT_WIDTH + 250;
+pub const BUT_DRY_RUN_CHECK_HEIGHT: i32 = BUT_HEIGHT;
+
+pub const BUT_PRERELEASE_CHECK_WIDTH: i32 = BUT_WIDTH + 175;
+pub const BUT_PRERELEASE_CHECK_HEIGHT: i32 = BUT_HEIGHT;
+
+pub const BUT_DARK_THEME_CHECK_WIDTH: i32 = BUT_WIDTH;
+pub const BUT_DARK_THEME_CHECK_HEIGHT: i32 = BUT_HEIGHT;
+
 // padding of the frame within buttons
 pub const BUT_PADDING: i32 = 3;
 // Spacing between teh buttons
@@ -65,18 +139,10 @@ pub const BUT_SPACING: i32 = 5;
 pub const BUT_FONT_SIZE: i32 = 16;
 pub const BUT_FONT: Font = Font::HelveticaBold;
 
-pub const BUT_ABORT_LABEL: &str = "Abort";
-pub const BUT_BACK_LABEL: &str = "@< Back ";
-pub const BUT_CONTINUE_LABEL: &str = " Continue@>";
-pub const BUT_SELECT_DIR_LABEL: &str = "Browse @fileopen";
-pub const BUT_DLX_VER_CHECK_LABEL: &str = "Deluxe version (pre-installed spritepacks)";
-pub const BUT_INSTALL_SPR_CHECK_LABEL: &str = "Download spritepacks (separate download into '/spritepacks')";
-pub const BUT_INSTALL_LABEL: &str = "Install";
-pub const BUT_OK_LABEL: &str = "Ok";
-pub const BUT_EXIT_LABEL: &str = "Exit";
 
 pub const BUT_ALERT_WIN_PADDING: i32 = 10;
 pub const BUT_MSG_WIN_PADDING: i32 = BUT_ALERT_WIN_PADDING;
+pub const BUT_CHOICE_WIN_PADDING: i32 = BUT_ALERT_WIN_PADDING;
 pub const BUT_PACK_YPADDING: i32 = INNER_WIN_CONTENT_YPADDING;
 
 
@@ -95,6 +161,29 @@ pub const MID_FRAME_LABEL_SIZE: i32 = TOP_FRAME_LABEL_SIZE;
 
 pub const MSG_FRAME_LABEL_SIZE: i32 = LABEL_SIZE_MED;
 
+pub const WARN_FRAME_LABEL_SIZE: i32 = LABEL_SIZE_SMALL;
+
+
+pub const DL_SIZE_FRAME_WIDTH: i32 = INNER_WIN_WIDTH - 2*INNER_WIN_CONTENT_XPADDING;
+pub const DL_SIZE_FRAME_HEIGHT: i32 = 25;
+pub const DL_SIZE_FRAME_LABEL_SIZE: i32 = LABEL_SIZE_SMALL;
+
+// Lists the kept archives at the bottom of the done window
+pub const KEPT_ARCHIVES_FRAME_WIDTH: i32 = MID_FRAME_WIDTH - 2*INNER_WIN_CONTENT_XPADDING;
+pub const KEPT_ARCHIVES_FRAME_HEIGHT: i32 = 70;
+pub const KEPT_ARCHIVES_FRAME_XPOS: i32 = MID_FRAME_XPOS + INNER_WIN_CONTENT_XPADDING;
+pub const KEPT_ARCHIVES_FRAME_YPOS: i32 = MID_FRAME_YPOS + MID_FRAME_HEIGHT - KEPT_ARCHIVES_FRAME_HEIGHT;
+pub const KEPT_ARCHIVES_FRAME_LABEL_SIZE: i32 = LABEL_SIZE_SMALL;
+
+
+pub const PROXY_INPUT_HEIGHT: i32 = 30;
+pub const PROXY_INPUT_TEXT_SIZE: i32 = LABEL_SIZE_SMALL;
+
+pub const SPEED_LIMIT_INPUT_WIDTH: i32 = 70;
+pub const SPEED_LIMIT_INPUT_HEIGHT: i32 = BUT_HEIGHT;
+pub const SPEED_LIMIT_INPUT_TEXT_SIZE: i32 = BUT_FONT_SIZE;
+pub const SPEED_LIMIT_LABEL_SIZE: i32 = LABEL_SIZE_SMALL;
+
 
 // Text display constants
 pub const TXT_DISP_XPOS: i32 = INNER_WIN_CONTENT_XPADDING;
@@ -102,6 +191,21 @@ pub const TXT_DISP_YPOS: i32 = MID_FRAME_YPOS;
 pub const TXT_DISP_WIDTH: i32 = INNER_WIN_WIDTH - 2*INNER_WIN_CONTENT_XPADDING;
 pub const TXT_DISP_HEIGHT: i32 = MID_FRAME_HEIGHT;
 
+// The welcome window shows the release notes under a shorter mid frame
+pub const WELCOME_MID_FRAME_HEIGHT: i32 = 110;
+
+pub const RELEASE_NOTES_TXT_XPOS: i32 = INNER_WIN_CONTENT_XPADDING;
+pub const RELEASE_NOTES_TXT_YPOS: i32 = MID_FRAME_YPOS + WELCOME_MID_FRAME_HEIGHT;
+pub const RELEASE_NOTES_TXT_WIDTH: i32 = TXT_DISP_WIDTH;
+pub const RELEASE_NOTES_TXT_HEIGHT: i32 = INNER_WIN_HEIGHT - RELEASE_NOTES_TXT_YPOS - 3*BUT_HEIGHT - 3*BUT_SPACING - BUT_PACK_YPADDING;
+pub const RELEASE_NOTES_TXT_SIZE: i32 = LABEL_SIZE_SMALL;
+
+pub const SUMMARY_TXT_SIZE: i32 = LABEL_SIZE_MED;
+// The summary leaves room for the button to change the install dir
+pub const SUMMARY_TXT_HEIGHT: i32 = TXT_DISP_HEIGHT - BUT_HEIGHT - BUT_SPACING;
+pub const SUMMARY_BUT_CHANGE_DIR_XPOS: i32 = TXT_DISP_XPOS + TXT_DISP_WIDTH - BUT_WIDTH;
+pub const SUMMARY_BUT_CHANGE_DIR_YPOS: i32 = TXT_DISP_YPOS + SUMMARY_TXT_HEIGHT + BUT_SPACING;
+
 
 // Text consts
 pub const SEL_DIR_TXT_XPOS: i32 = INNER_WIN_CONTENT_XPADDING;
@@ -110,16 +214,51 @@ pub const SEL_DIR_TXT_WIDTH: i32 = INNER_WIN_WIDTH - 2*INNER_WIN_CONTENT_XPADDIN
 pub const SEL_DIR_TXT_HEIGHT: i32 = 28;
 pub const SEL_DIR_TXT_SIZE: i32 = 18;
 
-pub const SEL_DIR_DLG_PROMPT: &str = "Select Doki Doki Literature Club directory";
+pub const VER_CHOICE_XPOS: i32 = SEL_DIR_TXT_XPOS;
+pub const VER_CHOICE_YPOS: i32 = INNER_WIN_HEIGHT/2 - VER_CHOICE_HEIGHT/2;
+pub const VER_CHOICE_WIDTH: i32 = SEL_DIR_TXT_WIDTH;
+pub const VER_CHOICE_HEIGHT: i32 = SEL_DIR_TXT_HEIGHT;
+pub const VER_CHOICE_TXT_SIZE: i32 = SEL_DIR_TXT_SIZE;
+
+pub const LANG_CHOICE_WIDTH: i32 = BUT_WIDTH;
+pub const LANG_CHOICE_HEIGHT: i32 = SEL_DIR_TXT_HEIGHT;
+pub const LANG_CHOICE_XPOS: i32 = INNER_WIN_WIDTH - LANG_CHOICE_WIDTH - WIN_PADDING;
+pub const LANG_CHOICE_YPOS: i32 = WIN_PADDING;
+pub const LANG_CHOICE_TXT_SIZE: i32 = LABEL_SIZE_SMALL;
+
 
 pub const LABEL_SIZE_LARGE: i32 = 28;
 pub const LABEL_SIZE_MED: i32 = 20;
+pub const LABEL_SIZE_SMALL: i32 = 14;
 
 
 // Progress bar consts
 pub const PB_WIDTH: i32 = INNER_WIN_WIDTH - 2*INNER_WIN_CONTENT_XPADDING;
 pub const PB_HEIGHT: i32 = BUT_HEIGHT;
 
+pub const PB_XPOS: i32 = INNER_WIN_CONTENT_XPADDING;
+pub const PB_YPOS: i32 = WIN_HEIGHT/2-PB_HEIGHT/2;
+pub const COMPACT_PB_YPOS: i32 = TOP_FRAME_YPOS + TOP_FRAME_HEIGHT + PB_PHASE_HEIGHT + 2*BUT_SPACING;
+pub const PB_LABEL_SIZE: i32 = 16;
+pub const PB_MARQUEE_INTERVAL: f64 = 0.03;
+pub const PB_MARQUEE_STEP: f64 = 0.02;
+
+pub const PB_PHASE_WIDTH: i32 = PB_WIDTH;
+pub const PB_PHASE_HEIGHT: i32 = 30;
+pub const PB_PHASE_YPOS: i32 = PB_YPOS - PB_PHASE_HEIGHT - BUT_SPACING;
+pub const COMPACT_PB_PHASE_YPOS: i32 = COMPACT_PB_YPOS - PB_PHASE_HEIGHT - BUT_SPACING;
+pub const PB_PHASE_LABEL_SIZE: i32 = LABEL_SIZE_MED;
+
+pub const PB_VERSION_WIDTH: i32 = PB_WIDTH;
+pub const PB_VERSION_HEIGHT: i32 = 30;
+pub const PB_VERSION_YPOS: i32 = PB_PHASE_YPOS - PB_VERSION_HEIGHT - BUT_SPACING;
+pub const PB_VERSION_LABEL_SIZE: i32 = LABEL_SIZE_MED;
+
+pub const PB_INFO_WIDTH: i32 = PB_WIDTH;
+pub const PB_INFO_HEIGHT: i32 = 25;
+pub const PB_INFO_LABEL_SIZE: i32 = 16;
+pub const PB_INFO_MAX_ETA: Duration = Duration::from_secs(24*60*60);
+
 
 // Slider consts
 pub const SCROLL_AMOUNT: f64 = 3.0;
@@ -133,41 +272,54 @@ pub const LICENSE_SLIDER_SIZE: f32 = 0.2;
 
 
 // Color constants
-pub const C_BLACK: Color = Color::Black;
-pub const C_WHITE: Color = Color::White;
-
-pub const C_DDLC_PEACH: Color = Color::from_hex(0xffaa99);
-pub const C_DDLC_WHITE_IDLE: Color = Color::from_hex(0xffe6f4);
-pub const C_DDLC_PINK_IDLE: Color = Color::from_hex(0xffbde1);
-pub const C_DDLC_PINK_DARK: Color = Color::from_hex(0xbb5599);
-
-pub const C_DDLC_WHITE_ACT: Color = Color::from_hex(0xffffff);
+// NOTE: the themed colors are slots in the FLTK colormap (16-31 are free to use),
+// this way we can change them at runtime and all widgets pick them up
+// The names are for the light theme
+pub const C_BLACK: Color = Color::from_rgbi(16);
+pub const C_WHITE: Color = Color::from_rgbi(17);
+
+pub const C_DDLC_PEACH: Color = Color::from_rgbi(18);
+pub const C_DDLC_WHITE_IDLE: Color = Color::from_rgbi(19);
+pub const C_DDLC_PINK_IDLE: Color = Color::from_rgbi(20);
+pub const C_DDLC_PINK_DARK: Color = Color::from_rgbi(21);
+
+pub const C_DDLC_WHITE_ACT: Color = Color::from_rgbi(22);
 pub const C_DDLC_PINK_ACT: Color = C_DDLC_PINK_IDLE;
 
 pub const C_BRIGHT_GREEN: Color = Color::from_hex(0x00ff00);
 
+// Hex values of the themed colors, in the same order as the slots above
+const LIGHT_PALETTE: [u32; 7] = [0x000000, 0xffffff, 0xffaa99, 0xffe6f4, 0xffbde1, 0xbb5599, 0xffffff];
+const DARK_PALETTE: [u32; 7] = [0xf2e4ec, 0x3a3a3a, 0xffaa99, 0x2b2530, 0x6e3a5a, 0xff9fd2, 0x3f3645];
 
-// Raw text
-pub const WELCOME_TOP_FRAME_LABEL: &str = "Welcome to MAS installer";
-pub const WELCOME_MID_FRAME_LABEL: &str = concat!(
-    "This program will install the latest version\n",
-    "of the Monika After Story mod\n",
-    "on your computer"
-);
-pub const LICENSE_FRAME_LABEL: &str = "By continuing you agree with our license";
-pub const SELECT_DIR_FRAME_LABEL: &str = "Select Doki Doki Literature Club directory";
-pub const OPTIONS_FRAME_LABEL: &str = "Select additional settings";
-pub const PROGRESS_FRAME_LABEL: &str = "Installing. Please wait";
-pub const ABORT_TOP_FRAME_LABEL: &str = "Aborted";
-pub const ABORT_MID_FRAME_LABEL: &str = concat!(
-    "Installation has been aborted.\n",
-    "Any already extracted files will remain"
-);
-pub const DONE_TOP_FRAME_LABEL: &str = "Finished";
-pub const DONE_MID_FRAME_LABEL: &str = concat!(
-    "Monika After Story has been successfully\n",
-    "installed on your computer"
-);
+
+/// Color themes of the app
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    Light,
+    Dark
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        return Self::Light;
+    }
+}
+
+/// Sets the colors of the given theme,
+/// already shown windows need to be redrawn
+pub fn set_theme(theme: Theme) {
+    let palette = match theme {
+        Theme::Light => LIGHT_PALETTE,
+        Theme::Dark => DARK_PALETTE
+    };
+    let slots = [C_BLACK, C_WHITE, C_DDLC_PEACH, C_DDLC_WHITE_IDLE, C_DDLC_PINK_IDLE, C_DDLC_PINK_DARK, C_DDLC_WHITE_ACT];
+    for (slot, hex) in slots.into_iter().zip(palette) {
+        let (r, g, b) = Color::from_hex(hex).to_rgb();
+        app::set_color(slot, r, g, b);
+    }
+}
 
 
 // Define images
@@ -0,0 +1,21 @@
+/// Tests for the app logic that doesn't need the GUI
+
+use super::*;
+
+
+#[test]
+fn next_window_id_stops_at_the_last_window() {
+    assert_eq!(_get_next_window_id(WELCOME_WIN_ID, 7), Some(1));
+    assert_eq!(_get_next_window_id(SUMMARY_WIN_ID, 7), Some(PROGRESS_WIN_ID));
+    assert_eq!(_get_next_window_id(PROGRESS_WIN_ID, 7), None);
+    assert_eq!(_get_next_window_id(10, 7), None);
+    assert_eq!(_get_next_window_id(usize::MAX, 7), None);
+    assert_eq!(_get_next_window_id(0, 0), None);
+}
+
+#[test]
+fn previous_window_id_stops_at_the_first_window() {
+    assert_eq!(_get_previous_window_id(WELCOME_WIN_ID), None);
+    assert_eq!(_get_previous_window_id(1), Some(WELCOME_WIN_ID));
+    assert_eq!(_get_previous_window_id(PROGRESS_WIN_ID), Some(SUMMARY_WIN_ID));
+}
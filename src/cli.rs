@@ -0,0 +1,367 @@
+/// The module that implements headless (no GUI) mode for scripted installs
+
+use std::{
+    env,
+    path::PathBuf,
+    sync::Mutex,
+    thread
+};
+
+use crate::{
+    app::{
+        state::{build_thread_safe_state, ThreadSafeState},
+        strings::get_strings
+    },
+    config,
+    platform,
+    reporter::ProgressReporter,
+    utils,
+    InstallResult,
+    Message
+};
+
+
+const HEADLESS_FLAG: &str = "--headless";
+const COMPACT_FLAG: &str = "--compact";
+
+pub const USAGE: &str = concat!(
+    "Usage: mas-installer --headless --dir <path> [options]\n",
+    "\n",
+    "Options:\n",
+    "    --dir <path>        DDLC directory to install into (required)\n",
+    "    --create-dir        create the directory if it doesn't exist\n",
+    "    --deluxe            install the deluxe version\n",
+    "    --no-spritepacks    don't download the spritepacks\n",
+    "    --spritepacks-only  only install the spritepacks onto an existing MAS\n",
+    "    --copy-from <path>  copy this DDLC directory into --dir, then install into the copy\n",
+    "    --shortcut          create a desktop shortcut to the game\n",
+    "    --keep-archives     keep the downloaded archives in the DDLC directory\n",
+    "    --remove-old-files  remove the files the previous version left behind after an update\n",
+    "    --dry-run           only list the files that would be installed\n",
+    "    --verify            check the installed files against the release\n",
+    "    --repair            same as --verify, then restore the bad files\n",
+    "    --version <tag>     install the release with the given tag instead of the latest\n",
+    "    --temp-dir <path>   keep the partial downloads in this directory\n",
+    "    --max-retries <n>   how many times a failed request is retried (default 5)\n",
+    "    --help              show this message"
+);
+
+
+/// Struct representing parsed command-line arguments
+#[derive(Debug, Default)]
+pub struct CliArgs {
+    pub dir: Option<PathBuf>,
+    pub create_dir: bool,
+    pub deluxe: bool,
+    pub no_spritepacks: bool,
+    pub spritepacks_only: bool,
+    pub copy_from: Option<PathBuf>,
+    pub shortcut: bool,
+    pub keep_archives: bool,
+    pub remove_old_files: bool,
+    pub dry_run: bool,
+    pub verify: bool,
+    pub repair: bool,
+    pub version: Option<String>,
+    pub temp_dir: Option<PathBuf>,
+    pub max_retries: Option<u32>,
+    pub help: bool
+}
+
+
+/// Checks if the app was launched in headless mode
+pub fn is_headless() -> bool {
+    return env::args().skip(1).any(|arg| arg == HEADLESS_FLAG);
+}
+
+/// Checks if the app was launched in compact mode,
+/// it's a GUI mode, so it's not one of the headless options
+pub fn is_compact() -> bool {
+    return env::args().skip(1).any(|arg| arg == COMPACT_FLAG);
+}
+
+/// Parses the given command-line arguments (without the program name)
+pub fn parse_args(args: impl Iterator<Item = String>) -> Result<CliArgs, String> {
+    let mut rv = CliArgs::default();
+    let mut args = args;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            HEADLESS_FLAG => {},
+            "--dir" => {
+                let dir = args.next().ok_or("missing value for --dir")?;
+                rv.dir = Some(PathBuf::from(dir));
+            },
+            "--create-dir" => rv.create_dir = true,
+            "--deluxe" => rv.deluxe = true,
+            "--no-spritepacks" => rv.no_spritepacks = true,
+            "--spritepacks-only" => rv.spritepacks_only = true,
+            "--copy-from" => {
+                let dir = args.next().ok_or("missing value for --copy-from")?;
+                rv.copy_from = Some(PathBuf::from(dir));
+            },
+            "--shortcut" => rv.shortcut = true,
+            "--keep-archives" => rv.keep_archives = true,
+            "--remove-old-files" => rv.remove_old_files = true,
+            "--dry-run" => rv.dry_run = true,
+            "--verify" => rv.verify = true,
+            "--repair" => rv.repair = true,
+            "--version" => {
+                let tag = args.next().ok_or("missing value for --version")?;
+                rv.version = Some(tag);
+            },
+            "--temp-dir" => {
+                let dir = args.next().ok_or("missing value for --temp-dir")?;
+                rv.temp_dir = Some(PathBuf::from(dir));
+            },
+            "--max-retries" => {
+                let value = args.next().ok_or("missing value for --max-retries")?;
+                let value = value.parse::<u32>().map_err(|_| format!("invalid --max-retries: {value}"))?;
+                rv.max_retries = Some(value);
+            },
+            "--help" | "-h" => rv.help = true,
+            _ => return Err(format!("unknown argument: {arg}"))
+        };
+    }
+    return Ok(rv);
+}
+
+/// Prints the progress of the installation to stdout
+struct CliReporter {
+    last_percentage: Mutex<i32>
+}
+
+impl CliReporter {
+    fn new() -> Self {
+        return Self { last_percentage: Mutex::new(-1) };
+    }
+}
+
+impl ProgressReporter for CliReporter {
+    fn report(&self, msg: Message) {
+        match msg {
+            Message::UpdateProgressBar(val) => self.progress(val),
+            Message::ProgressIndeterminate => {
+                println!("  Download size is unknown, progress is unavailable...");
+            },
+            Message::RetryingDownload(attempt) => {
+                println!("  Connection issues, retrying ({attempt})...");
+            },
+            Message::Throttled(delay) => {
+                println!("  The server asked us to slow down, waiting {}...", utils::format_duration(delay));
+            },
+            msg => self.phase(msg)
+        };
+    }
+
+    fn phase(&self, msg: Message) {
+        match msg {
+            Message::Preparing => println!("Preparing..."),
+            Message::FetchingReleaseInfo => println!("Fetching release info..."),
+            Message::Copying => println!("Copying DDLC..."),
+            Message::BackingUp => println!("Backing up..."),
+            Message::RemovingOldInstall => println!("Removing old installation..."),
+            Message::Downloading => println!("Downloading..."),
+            Message::Extracting => println!("Extracting..."),
+            Message::DownloadingSpr => println!("Downloading spritepacks..."),
+            Message::ExtractingSpr => println!("Extracting spritepacks..."),
+            Message::UsingCachedDownload => println!("Using a cached download..."),
+            Message::Verifying => println!("Verifying files..."),
+            Message::RemovingStaleFiles => println!("Removing old files..."),
+            Message::CleaningUp => println!("Cleaning up..."),
+            _ => {}
+        };
+    }
+
+    fn progress(&self, value: f64) {
+        // Print every 10% so we don't spam the output
+        let percentage = (value * 10.0).floor() as i32 * 10;
+        let mut last_percentage = self.last_percentage.lock().unwrap();
+        if percentage != *last_percentage {
+            *last_percentage = percentage;
+            println!("  {percentage}%");
+        }
+    }
+}
+
+/// Runs the installer without GUI, returns the exit code
+pub fn run_headless(args: CliArgs) -> i32 {
+    if args.help {
+        println!("{USAGE}");
+        return 0;
+    }
+
+    let dir = match args.dir.as_deref().map(utils::normalize_path) {
+        Some(Ok(dir)) => dir,
+        Some(Err(e)) => {
+            eprintln!("invalid --dir: {e}");
+            return 2;
+        },
+        None => {
+            eprintln!("missing --dir\n\n{USAGE}");
+            return 2;
+        }
+    };
+    let copy_from = match args.copy_from.as_deref().map(utils::normalize_path) {
+        Some(Ok(source)) => Some(source),
+        Some(Err(e)) => {
+            eprintln!("invalid --copy-from: {e}");
+            return 2;
+        },
+        None => None
+    };
+    if let Some(ref source) = copy_from {
+        if args.spritepacks_only || args.verify || args.repair {
+            eprintln!("--copy-from can't be used with --spritepacks-only, --verify or --repair\n\n{USAGE}");
+            return 2;
+        }
+        if !utils::is_valid_ddlc_dir(source) {
+            eprintln!("{} doesn't appear to be a valid DDLC directory, there's nothing to copy", source.display());
+            return 2;
+        }
+        if !utils::is_usable_copy_target(&dir) {
+            eprintln!("{} isn't empty, --copy-from needs a new or empty directory", dir.display());
+            return 2;
+        }
+    }
+    // Nobody to ask, so it has to be asked for explicitly
+    if !dir.is_dir() && !args.create_dir {
+        eprintln!("{} doesn't exist, pass --create-dir to create it", dir.display());
+        return 2;
+    }
+    if args.spritepacks_only && (args.no_spritepacks || args.dry_run) {
+        eprintln!("--spritepacks-only can't be used with --no-spritepacks or --dry-run\n\n{USAGE}");
+        return 2;
+    }
+    if args.shortcut && !platform::get_capabilities().shortcuts {
+        eprintln!("Warning: desktop shortcuts aren't supported on this system, --shortcut is ignored");
+    }
+    // The copy becomes a DDLC dir
+    if copy_from.is_none() && !utils::is_valid_ddlc_dir(&dir) {
+        eprintln!("Warning: {} doesn't appear to be a valid DDLC directory", dir.display());
+    }
+
+    // The preferences get overridden below, but we need the proxy settings
+    let state = build_thread_safe_state(&config::load_config());
+    {
+        let mut app_state = state.lock().unwrap();
+        let is_valid = utils::is_valid_ddlc_dir(&dir);
+        app_state.set_extraction_dir(dir);
+        app_state.set_ddlc_dir_valid(Some(is_valid));
+        // Nobody to ask, we've warned about the dir above
+        app_state.set_populated_dir_confirmed(true);
+        app_state.set_create_dir_confirmed(args.create_dir);
+        app_state.set_deluxe_ver_flag(args.deluxe);
+        app_state.set_install_spr_flag(!args.no_spritepacks);
+        app_state.set_spr_only_flag(args.spritepacks_only);
+        app_state.set_copy_source(copy_from);
+        app_state.set_shortcut_flag(args.shortcut);
+        app_state.set_keep_archives_flag(args.keep_archives);
+        // Nobody to review the list, so only on request
+        app_state.set_remove_stale_files(Some(args.remove_old_files));
+        app_state.set_dry_run_flag(args.dry_run);
+        app_state.set_release_tag(args.version);
+        if args.temp_dir.is_some() {
+            app_state.set_temp_dir(args.temp_dir);
+        }
+        if let Some(value) = args.max_retries {
+            app_state.set_max_retries(value);
+        }
+        // Nobody to ask, the partial downloads are verified anyway
+        app_state.set_resume_flag(true);
+    }
+
+    if args.verify || args.repair {
+        return _run_verification(&state, args.repair);
+    }
+
+    let th_handle = utils::install_game_in_thread(CliReporter::new(), &state);
+
+    return match th_handle.join() {
+        Ok(Ok(_)) if args.dry_run => {
+            if let Some(report) = state.lock().unwrap().get_dry_run_report() {
+                for path in report.paths.iter() {
+                    println!("{}", path.display());
+                }
+                println!(
+                    "Dry run is complete: {} files and folders ({})",
+                    report.paths.len(),
+                    utils::format_size(report.total_size as f64)
+                );
+            }
+            0
+        },
+        Ok(Ok(_)) => {
+            for path in state.lock().unwrap().get_kept_archives() {
+                println!("Kept the archive at {}", path.display());
+            }
+            println!("Installation is complete!");
+            0
+        },
+        Ok(Err(e)) => {
+            eprintln!("Installation failed: {e}");
+            if let Some(hint) = get_strings().get_error_hint(e.get_kind()) {
+                eprintln!("{hint}");
+            }
+            1
+        },
+        Err(e) => {
+            eprintln!("Installer thread panicked: {:?}", e);
+            1
+        }
+    };
+}
+
+/// Waits for the worker thread, prints the error if it failed
+fn _join_worker(th_handle: thread::JoinHandle<InstallResult>, action: &str) -> Result<(), i32> {
+    return match th_handle.join() {
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err(e)) => {
+            eprintln!("{action} failed: {e}");
+            if let Some(hint) = get_strings().get_error_hint(e.get_kind()) {
+                eprintln!("{hint}");
+            }
+            Err(1)
+        },
+        Err(e) => {
+            eprintln!("Installer thread panicked: {:?}", e);
+            Err(1)
+        }
+    };
+}
+
+/// Verifies the existing install and repairs it if asked, returns the exit code
+fn _run_verification(state: &ThreadSafeState, repair: bool) -> i32 {
+    if let Err(code) = _join_worker(utils::verify_install_in_thread(CliReporter::new(), state), "Verification") {
+        return code;
+    }
+    let report = match state.lock().unwrap().get_verify_report().cloned() {
+        Some(report) => report,
+        None => return 1
+    };
+    for name in report.missing.iter() {
+        println!("missing: {name}");
+    }
+    for name in report.damaged.iter() {
+        println!("damaged: {name}");
+    }
+    println!(
+        "Checked {} files against {}: {} missing, {} damaged",
+        report.checked,
+        report.tag,
+        report.missing.len(),
+        report.damaged.len()
+    );
+    if report.is_intact() {
+        return 0;
+    }
+    if !repair {
+        return 1;
+    }
+
+    if let Err(code) = _join_worker(utils::verify_install_in_thread(CliReporter::new(), state), "Repair") {
+        return code;
+    }
+    println!("Repair is complete!");
+    return 0;
+}
@@ -0,0 +1,148 @@
+/// The module that implements the config file of the installer
+
+use std::{
+    fs::{
+        create_dir_all,
+        read_to_string,
+        write as write_file
+    },
+    io::{self, ErrorKind},
+    path::PathBuf
+};
+
+use serde::{
+    Deserialize,
+    Serialize
+};
+
+use crate::app::{strings::Language, styles::Theme};
+
+
+const CONFIG_DIR_NAME: &str = "mas-installer";
+const CONFIG_FILE_NAME: &str = "config.toml";
+
+
+/// Struct representing proxy settings
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProxyConfig {
+    /// Proxy url, e.g. "http://proxy.example.com:8080"
+    pub url: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>
+}
+
+/// Struct representing the position and size of the main window
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32
+}
+
+
+/// Struct representing the config file
+/// the user preferences are optional, the defaults are used if they're missing
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// The last used extraction dir
+    pub extraction_dir: Option<PathBuf>,
+    pub deluxe_version: Option<bool>,
+    pub install_spritepacks: Option<bool>,
+    /// Names of the spritepack bundles to install, the combined bundle if missing
+    pub spritepack_bundles: Option<Vec<String>>,
+    /// Whether each spritepack bundle goes into its own subfolder, off if missing
+    pub spritepack_subfolders: Option<bool>,
+    pub theme: Option<Theme>,
+    /// The language picked by the user, detected from the OS locale if missing
+    pub language: Option<Language>,
+    /// Size of the download chunks in KiB, auto-tuned if missing
+    pub chunk_size_kb: Option<u64>,
+    /// Download speed limit in MB/s, unlimited if missing
+    pub speed_limit_mb: Option<f64>,
+    /// Whether to check the extracted files against the archive, off if missing
+    pub thorough_verification: Option<bool>,
+    /// Network timeouts in seconds, the defaults are used if missing
+    pub connect_timeout_secs: Option<u64>,
+    pub read_timeout_secs: Option<u64>,
+    /// How long the install can go without progress in seconds, 0 disables the check
+    pub stall_timeout_secs: Option<u64>,
+    /// How many times a failed request is retried, 5 if missing
+    pub max_retries: Option<u32>,
+    /// Whether to keep the downloaded assets for the next install, on if missing
+    pub cache_downloads: Option<bool>,
+    /// Max size of the download cache in MiB
+    pub cache_size_mb: Option<u64>,
+    /// Whether to download spritepacks while MAS is being extracted, off if missing
+    pub parallel_downloads: Option<bool>,
+    /// Where to keep partial downloads, the user's cache dir if missing
+    pub temp_dir: Option<PathBuf>,
+    /// Where to save the downloaded archives if the user keeps them, the install dir if missing
+    pub keep_archives_dir: Option<PathBuf>,
+    /// The GitHub repo to install from, MAS_ORG/MAS_REPO env vars take priority,
+    /// the official repo is used if missing or invalid
+    pub github_org: Option<String>,
+    pub github_repo: Option<String>,
+    /// Where to extract the spritepacks, relative to the DDLC dir, "spritepacks" if missing or invalid
+    pub spritepacks_dir: Option<PathBuf>,
+    /// Whether to skip the wizard and install with these preferences
+    /// showing only the progress, off if missing
+    pub compact_mode: Option<bool>,
+    // NOTE: tables must go after plain values in TOML
+    pub proxy: ProxyConfig,
+    /// Where the main window was when the app was closed, centered if missing
+    pub window_geometry: Option<WindowGeometry>
+}
+
+
+/// Returns the path to the config file, None if the OS doesn't have a config dir
+pub fn get_config_path() -> Option<PathBuf> {
+    return dirs::config_dir().map(
+        |dir| dir.join(CONFIG_DIR_NAME).join(CONFIG_FILE_NAME)
+    );
+}
+
+/// Loads the config file, falls back to the default config
+/// if the file doesn't exist or is invalid
+pub fn load_config() -> Config {
+    let path = match get_config_path() {
+        Some(path) => path,
+        None => return Config::default()
+    };
+
+    let content = match read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) => {
+            if e.kind() != ErrorKind::NotFound {
+                eprintln!("Failed to read config {}: {e}", path.display());
+            }
+            return Config::default();
+        }
+    };
+
+    return match toml::from_str(&content) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Failed to parse config {}: {e}", path.display());
+            Config::default()
+        }
+    };
+}
+
+/// Saves the config file, creates the config dir if needed
+pub fn save_config(config: &Config) -> Result<(), io::Error> {
+    let path = get_config_path()
+        .ok_or_else(|| io::Error::new(ErrorKind::NotFound, "no config dir on this system"))?;
+
+    let content = toml::to_string(config)
+        .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?;
+
+    if let Some(parent_dir) = path.parent() {
+        create_dir_all(parent_dir)?;
+    }
+    write_file(path, content)?;
+    return Ok(());
+}
@@ -1,7 +1,9 @@
 
 use std::io::Error as IOError;
+use std::path::PathBuf;
 use std::error::Error as StdError;
 use std::fmt;
+use std::time::{Duration, SystemTime};
 
 use zip::result::ZipError;
 use reqwest::{
@@ -27,6 +29,26 @@ pub enum DownloadError {
     /// Server returned invalid status code
     /// while downloading the assets
     InvalidStatusCode(StatusCode),
+    /// The downloaded file size doesn't match the content length,
+    /// the download might be corrupted
+    SizeMismatch { expected: u128, actual: u128 },
+    /// The downloaded file hash doesn't match the one GitHub provided
+    ChecksumMismatch { expected: String, actual: String },
+    /// The server ignored our ranged request and the fallback
+    /// full download failed too, contains the error of the latter
+    RangeNotSupported(Box<DownloadError>),
+    /// The archive isn't a zip or .tar.gz file (e.g. we downloaded an HTML error page),
+    /// contains the first bytes of the file
+    NotAnArchive(Vec<u8>),
+    /// The server asked us to slow down (429/503),
+    /// contains the delay from the Retry-After header if it was provided
+    Throttled(Option<Duration>),
+    /// The connection stalled and kept timing out after all the retries,
+    /// contains the url
+    Timeout(String),
+    /// The server answered the ranged request without any data,
+    /// contains the start of the range
+    EmptyChunk(u128),
     /// General IO failure, couldn't write/read
     IOError(IOError)
 }
@@ -42,10 +64,28 @@ impl From<IOError> for DownloadError {
     }
 }
 
+impl DownloadError {
+    /// Returns true if this error is likely temporary,
+    /// e.g. connection issues or server overload
+    pub fn is_transient(&self) -> bool {
+        return match self {
+            Self::RequestError(err) => {
+                err.is_connect() || err.is_timeout() || err.is_request() || err.is_body()
+            },
+            Self::InvalidStatusCode(code) => {
+                code.is_server_error() || *code == StatusCode::TOO_MANY_REQUESTS
+            },
+            Self::Throttled(_) | Self::Timeout(_) | Self::EmptyChunk(_) => true,
+            _ => false
+        };
+    }
+}
+
 impl StdError for DownloadError {
     fn source(&self) -> Option<&(dyn StdError + 'static)> {
         return match self {
             Self::RequestError(og_err) => Some(og_err),
+            Self::RangeNotSupported(og_err) => Some(og_err.as_ref()),
             Self::IOError(og_err) => Some(og_err),
             _ => None
         };
@@ -64,6 +104,27 @@ impl fmt::Display for DownloadError {
             Self::InvalidStatusCode(code) => {
                 write!(f, "GitHub returned invalid status code: {}", code)
             },
+            Self::SizeMismatch { expected, actual } => {
+                write!(f, "downloaded file size mismatch: expected {} bytes, got {} bytes", expected, actual)
+            },
+            Self::ChecksumMismatch { expected, actual } => {
+                write!(f, "downloaded file checksum mismatch: expected {}, got {}", expected, actual)
+            },
+            Self::RangeNotSupported(err) => {
+                write!(f, "server doesn't support partial downloads, and the full download failed: {}", err)
+            },
+            Self::NotAnArchive(bytes) => {
+                write!(f, "the archive is not a zip or .tar.gz file, it starts with: {:?}", String::from_utf8_lossy(bytes))
+            },
+            Self::Throttled(_) => {
+                write!(f, "the server is limiting the download rate, try again later")
+            },
+            Self::Timeout(url) => {
+                write!(f, "the connection timed out: {}", url)
+            },
+            Self::EmptyChunk(low_bound) => {
+                write!(f, "the server sent no data from byte {}", low_bound)
+            },
             Self::IOError(err) => {
                 write!(f, "failed to read/write data: {}", err)
             }
@@ -79,6 +140,23 @@ pub enum ExtractionError {
     ArchiveError(ZipError),
     /// Unsafe file path in the archive, possible attack?
     UnsafeFilepath(String),
+    /// Not enough free space to extract the archive
+    InsufficientSpace { needed: u64, available: u64 },
+    /// An extracted file is missing or its size doesn't match the archive
+    VerificationFailed { path: PathBuf },
+    /// The written file doesn't match the CRC32 of its zip entry,
+    /// most likely the disk failed to write it
+    CrcMismatch { path: PathBuf },
+    /// Can't create files in the install dir, e.g. it needs admin rights
+    NotWritable { path: PathBuf },
+    /// Couldn't create the missing install dir
+    CreateDirFailed { path: PathBuf, error: IOError },
+    /// Couldn't write an extracted file, e.g. the disk is full or it's not allowed
+    WriteFailed { path: PathBuf, source: IOError },
+    /// Couldn't read the data of an entry, the archive is most likely damaged
+    ArchiveRead { source: IOError },
+    /// The archive isn't a zip or .tar.gz file, contains the first bytes of the file
+    UnsupportedFormat(Vec<u8>),
     /// I/O error
     IOError(IOError)
 }
@@ -98,6 +176,7 @@ impl StdError for ExtractionError {
     fn source(&self) -> Option<&(dyn StdError + 'static)> {
         return match self {
             Self::ArchiveError(og_err) => Some(og_err),
+            Self::WriteFailed { source, .. } | Self::ArchiveRead { source } => Some(source),
             Self::IOError(og_err) => Some(og_err),
             _ => None
         };
@@ -113,6 +192,35 @@ impl fmt::Display for ExtractionError {
             Self::UnsafeFilepath(_) => {
                 write!(f, "found unsafe filepath in archive")
             },
+            Self::VerificationFailed { path } => {
+                write!(f, "extracted file is missing or incomplete: {}", path.display())
+            },
+            Self::CrcMismatch { path } => {
+                write!(f, "extracted file doesn't match its checksum in the archive: {}", path.display())
+            },
+            Self::NotWritable { path } => {
+                write!(f, "can't write into {}", path.display())
+            },
+            Self::CreateDirFailed { path, error } => {
+                write!(f, "can't create {}: {error}", path.display())
+            },
+            Self::WriteFailed { path, source } => {
+                write!(f, "failed to write {}: {source}", path.display())
+            },
+            Self::ArchiveRead { source } => {
+                write!(f, "failed to read the archive data: {source}")
+            },
+            Self::UnsupportedFormat(bytes) => {
+                write!(f, "the archive is not a zip or .tar.gz file, it starts with: {:?}", String::from_utf8_lossy(bytes))
+            },
+            Self::InsufficientSpace { needed, available } => {
+                write!(
+                    f,
+                    "not enough free space: need {}, only {} available",
+                    crate::utils::format_size(*needed as f64),
+                    crate::utils::format_size(*available as f64)
+                )
+            },
             Self::IOError(err) => {
                 write!(f, "failed to read/write data: {}", err)
             }
@@ -121,6 +229,53 @@ impl fmt::Display for ExtractionError {
 }
 
 
+/// Broad category of an installer error,
+/// lightweight enough to be sent through the channel
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ErrorKind {
+    /// Couldn't reach GitHub or the connection dropped
+    Network,
+    /// Couldn't connect through the proxy
+    Proxy,
+    /// GitHub is limiting our requests
+    RateLimited,
+    /// Ran out of disk space
+    DiskFull,
+    /// The downloaded file doesn't match the expected size/hash
+    ChecksumMismatch,
+    /// The archive is broken or isn't an archive at all
+    CorruptArchive,
+    /// No permission to write into the install dir
+    NotWritable,
+    /// Writing the extracted files failed for another reason
+    WriteFailed,
+    /// Anything else
+    Other
+}
+
+/// Returns true if the IO error means the disk is full
+fn _is_disk_full(err: &IOError) -> bool {
+    return err.raw_os_error().map(crate::platform::is_disk_full_code).unwrap_or(false);
+}
+
+impl DownloadError {
+    /// Returns the category of this error
+    pub fn get_kind(&self) -> ErrorKind {
+        return match self {
+            Self::RequestError(_) | Self::Timeout(_) => ErrorKind::Network,
+            Self::InvalidStatusCode(code) if *code == StatusCode::TOO_MANY_REQUESTS => ErrorKind::RateLimited,
+            Self::InvalidStatusCode(code) if code.is_server_error() => ErrorKind::Network,
+            Self::Throttled(_) => ErrorKind::RateLimited,
+            Self::SizeMismatch { .. } | Self::ChecksumMismatch { .. } => ErrorKind::ChecksumMismatch,
+            Self::RangeNotSupported(err) => err.get_kind(),
+            Self::NotAnArchive(_) => ErrorKind::CorruptArchive,
+            Self::IOError(err) if _is_disk_full(err) => ErrorKind::DiskFull,
+            _ => ErrorKind::Other
+        };
+    }
+}
+
+
 /// The "main" error type that can occur,
 /// represents an error occured during installation
 #[derive(Debug)]
@@ -131,12 +286,44 @@ pub enum InstallerError {
     CorruptedJSON(&'static str),
     /// JSON is missing some fields
     InvalidJson(SerdeError),
+    /// Couldn't find an asset matching the pattern in the release
+    AssetNotFound(&'static str),
+    /// GitHub API returned 404, the repo or the tag doesn't exist,
+    /// contains the requested url
+    ReleaseNotFound(String),
     /// Got invalid response/failed to send request
     RequestError(ReqError),
+    /// Invalid proxy or failed to connect to it
+    ProxyError(ReqError),
+    /// Couldn't reach GitHub before starting the install
+    NoConnectivity(ReqError),
+    /// GitHub API rate limit exceeded,
+    /// contains the time when the limit resets (if GitHub told us)
+    RateLimited { reset_at: Option<SystemTime> },
     /// General IO failure, couldn't write/read
     IOError(IOError),
+    /// Failed to back up the existing game dir
+    BackupFailed(IOError),
+    /// Can't use the dir for partial downloads, it's not writable
+    TempDirUnusable { path: PathBuf, err: IOError },
+    /// Not enough free space in the dir for partial downloads
+    InsufficientTempSpace { path: PathBuf, needed: u64, available: u64 },
+    /// The repo has no published releases yet, contains the repo
+    NoReleasesAvailable(String),
+    /// Couldn't read the list of installed files
+    ManifestNotFound(IOError),
+    /// Spritepacks-only install into a dir without MAS
+    MasNotInstalled(PathBuf),
+    /// The dir we were asked to copy isn't a DDLC dir
+    CopySourceInvalid(PathBuf),
+    /// The copy has to go into a new or empty dir
+    CopyTargetExists(PathBuf),
+    /// Failed to copy DDLC into the new dir
+    CopyFailed(IOError),
     /// Error occured during extraction
-    ExtractionError(ExtractionError)
+    ExtractionError(ExtractionError),
+    /// Nothing has happened for too long, contains the timeout
+    Stalled(Duration)
 }
 
 impl From<SerdeError> for InstallerError {
@@ -171,13 +358,96 @@ impl StdError for InstallerError {
             Self::DownloadError(og_err) => Some(og_err),
             Self::InvalidJson(og_err) => Some(og_err),
             Self::RequestError(og_err) => Some(og_err),
+            Self::ProxyError(og_err) => Some(og_err),
+            Self::NoConnectivity(og_err) => Some(og_err),
             Self::IOError(og_err) => Some(og_err),
+            Self::BackupFailed(og_err) => Some(og_err),
+            Self::CopyFailed(og_err) => Some(og_err),
+            Self::TempDirUnusable { err, .. } => Some(err),
+            Self::ManifestNotFound(og_err) => Some(og_err),
             Self::ExtractionError(og_err) => Some(og_err),
             _ => None
         };
     }
 }
 
+impl InstallerError {
+    /// Returns true if this error might go away if we try again,
+    /// the user gets to resume the download or start over
+    pub fn is_retriable(&self) -> bool {
+        return self.is_transient() || matches!(
+            self,
+            Self::DownloadError(
+                DownloadError::SizeMismatch { .. }
+                | DownloadError::ChecksumMismatch { .. }
+                | DownloadError::Timeout(_)
+                | DownloadError::EmptyChunk(_)
+            )
+            | Self::Stalled(_)
+        );
+    }
+
+    /// Returns true if this error is likely temporary and the request can be sent again,
+    /// e.g. connection issues, but not a missing release or the rate limit
+    pub fn is_transient(&self) -> bool {
+        return match self {
+            Self::DownloadError(err) => err.is_transient(),
+            Self::RequestError(err) => {
+                err.is_connect() || err.is_timeout() || err.is_request() || err.is_body()
+            },
+            _ => false
+        };
+    }
+
+    /// Returns the category of this error
+    pub fn get_kind(&self) -> ErrorKind {
+        return match self {
+            Self::DownloadError(err) => err.get_kind(),
+            Self::RequestError(_) | Self::NoConnectivity(_) | Self::Stalled(_) => ErrorKind::Network,
+            Self::ProxyError(_) => ErrorKind::Proxy,
+            Self::RateLimited { .. } => ErrorKind::RateLimited,
+            Self::IOError(err) | Self::BackupFailed(err) | Self::CopyFailed(err) if _is_disk_full(err) => ErrorKind::DiskFull,
+            Self::InsufficientTempSpace { .. } => ErrorKind::DiskFull,
+            Self::ExtractionError(ExtractionError::InsufficientSpace { .. }) => ErrorKind::DiskFull,
+            Self::ExtractionError(ExtractionError::IOError(err)) if _is_disk_full(err) => ErrorKind::DiskFull,
+            Self::ExtractionError(
+                ExtractionError::ArchiveError(_) | ExtractionError::ArchiveRead { .. } | ExtractionError::UnsupportedFormat(_)
+            ) => ErrorKind::CorruptArchive,
+            Self::ExtractionError(ExtractionError::WriteFailed { source, .. }) => {
+                match source.kind() {
+                    std::io::ErrorKind::PermissionDenied => ErrorKind::NotWritable,
+                    _ if _is_disk_full(source) => ErrorKind::DiskFull,
+                    _ => ErrorKind::WriteFailed
+                }
+            },
+            Self::ExtractionError(ExtractionError::NotWritable { .. }) => ErrorKind::NotWritable,
+            Self::ExtractionError(ExtractionError::CreateDirFailed { error, .. }) => {
+                match error.kind() {
+                    std::io::ErrorKind::PermissionDenied => ErrorKind::NotWritable,
+                    _ if _is_disk_full(error) => ErrorKind::DiskFull,
+                    _ => ErrorKind::Other
+                }
+            },
+            _ => ErrorKind::Other
+        };
+    }
+
+    /// Returns true if this error can be fixed by selecting another directory
+    pub fn needs_other_dir(&self) -> bool {
+        return matches!(
+            self,
+            Self::ExtractionError(
+                ExtractionError::InsufficientSpace { .. }
+                | ExtractionError::NotWritable { .. }
+                | ExtractionError::CreateDirFailed { .. }
+                | ExtractionError::WriteFailed { .. }
+            )
+            | Self::MasNotInstalled(_)
+            | Self::CopyTargetExists(_)
+        );
+    }
+}
+
 impl fmt::Display for InstallerError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         return match self {
@@ -190,14 +460,78 @@ impl fmt::Display for InstallerError {
             Self::InvalidJson(err) => {
                 write!(f, "recieved invalid JSON data from GitHub: {}", err)
             },
+            Self::AssetNotFound(pattern) => {
+                write!(f, "couldn't find asset matching '{}' in the release", pattern)
+            },
+            Self::ReleaseNotFound(url) => {
+                write!(f, "GitHub couldn't find the release, check the repository and the version: {}", url)
+            },
             Self::RequestError(err) => {
                 write!(f, "failed to request data: {}", err)
             },
+            Self::ProxyError(err) => {
+                write!(f, "failed to connect through the proxy, check your proxy settings: {}", err)
+            },
+            Self::NoConnectivity(err) => {
+                write!(f, "couldn't reach GitHub, check your internet connection: {}", err)
+            },
+            Self::RateLimited { reset_at } => {
+                let wait_mins = reset_at
+                    .and_then(|t| t.duration_since(SystemTime::now()).ok())
+                    .map(|d| d.as_secs() / 60 + 1);
+                match wait_mins {
+                    Some(mins) => write!(
+                        f,
+                        "GitHub rate limit exceeded: try again in {} min or set the GITHUB_TOKEN env var",
+                        mins
+                    ),
+                    None => write!(
+                        f,
+                        "GitHub rate limit exceeded: try again later or set the GITHUB_TOKEN env var"
+                    )
+                }
+            },
             Self::IOError(err) => {
                 write!(f, "I/O failure: {}", err)
             },
+            Self::BackupFailed(err) => {
+                write!(f, "failed to back up the game directory: {}", err)
+            },
+            Self::TempDirUnusable { path, err } => {
+                write!(f, "can't write temporary files into {}: {}", path.display(), err)
+            },
+            Self::InsufficientTempSpace { path, needed, available } => {
+                write!(
+                    f,
+                    "not enough free space for the downloads in {}: need {}, only {} available",
+                    path.display(),
+                    crate::utils::format_size(*needed as f64),
+                    crate::utils::format_size(*available as f64)
+                )
+            },
+            Self::NoReleasesAvailable(repo) => {
+                write!(f, "{} has no published releases yet", repo)
+            },
+            Self::ManifestNotFound(err) => {
+                write!(f, "couldn't read the list of installed files (was MAS installed by this installer?): {}", err)
+            },
+            Self::MasNotInstalled(path) => {
+                write!(f, "MAS isn't installed in {}, install it before the spritepacks", path.display())
+            },
+            Self::CopySourceInvalid(path) => {
+                write!(f, "{} isn't a DDLC directory, there's nothing to copy", path.display())
+            },
+            Self::CopyTargetExists(path) => {
+                write!(f, "{} isn't empty, the copy needs a new or empty directory", path.display())
+            },
+            Self::CopyFailed(err) => {
+                write!(f, "failed to copy DDLC: {}", err)
+            },
             Self::ExtractionError(err) => {
                 write!(f, "extraction failed: {}", err)
+            },
+            Self::Stalled(timeout) => {
+                write!(f, "the installation stalled, no progress in {}", crate::utils::format_duration(*timeout))
             }
         };
     }
@@ -266,3 +600,7 @@ impl fmt::Display for AudioError {
         };
     }
 }
+
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,33 @@
+/// Tests for the errors
+
+use super::*;
+
+
+#[test]
+fn download_failures_are_retriable() {
+    let retriable = [
+        InstallerError::DownloadError(DownloadError::SizeMismatch { expected: 10, actual: 5 }),
+        InstallerError::DownloadError(
+            DownloadError::ChecksumMismatch { expected: String::from("aa"), actual: String::from("bb") }
+        ),
+        InstallerError::DownloadError(DownloadError::Timeout(String::from("https://example.com/asset.zip"))),
+        InstallerError::DownloadError(DownloadError::EmptyChunk(1024)),
+        InstallerError::DownloadError(DownloadError::InvalidStatusCode(StatusCode::BAD_GATEWAY)),
+        InstallerError::DownloadError(DownloadError::Throttled(None)),
+        InstallerError::Stalled(Duration::from_secs(120))
+    ];
+    for err in retriable.iter() {
+        assert!(err.is_retriable(), "{err:?}");
+    }
+
+    let final_errors = [
+        InstallerError::DownloadError(DownloadError::InvalidStatusCode(StatusCode::NOT_FOUND)),
+        InstallerError::DownloadError(DownloadError::NotAnArchive(b"<html>".to_vec())),
+        InstallerError::AssetNotFound("spritepacks-*.zip"),
+        InstallerError::ReleaseNotFound(String::from("https://api.github.com/repos/org/repo/releases/latest")),
+        InstallerError::RateLimited { reset_at: None }
+    ];
+    for err in final_errors.iter() {
+        assert!(!err.is_retriable(), "{err:?}");
+    }
+}
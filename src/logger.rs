@@ -0,0 +1,204 @@
+/// The module that implements debug logging
+/// Quiet by default, set MAS_INSTALLER_LOG to a level (e.g. "debug") to enable it
+/// Also keeps the install log, which is always written for support requests
+
+use std::{
+    env,
+    error::Error as StdError,
+    fs::{create_dir_all, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH}
+};
+
+use log::{
+    Level,
+    LevelFilter,
+    Log,
+    Metadata,
+    Record
+};
+
+
+const LOG_LEVEL_ENV_VAR: &str = "MAS_INSTALLER_LOG";
+const LOG_FILE_NAME: &str = "mas-installer.log";
+const INSTALL_LOG_FILE_NAME: &str = "install.log";
+// Our module paths start with this, the dependencies only log warnings and errors
+const CRATE_TARGET: &str = "mas_installer";
+
+
+/// Logger that writes to stderr and tees into a file in the user's cache dir
+struct Logger {
+    file: Mutex<Option<File>>
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        if metadata.level() > log::max_level() {
+            return false;
+        }
+        return metadata.target().starts_with(CRATE_TARGET) || metadata.level() <= Level::Warn;
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let timestamp = _get_timestamp();
+        let line = format!("[{timestamp:.3}] {:<5} {}: {}", record.level(), record.target(), record.args());
+
+        eprintln!("{line}");
+        if let Some(file) = self.file.lock().unwrap().as_mut() {
+            // Nowhere to report the error, so just ignore it
+            writeln!(file, "{line}").ok();
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(file) = self.file.lock().unwrap().as_mut() {
+            file.flush().ok();
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref LOGGER: Logger = Logger { file: Mutex::new(None) };
+    // The install log of the current session, kept open between retries
+    static ref INSTALL_LOG: Mutex<Option<(PathBuf, File)>> = Mutex::new(None);
+}
+
+
+/// Returns seconds since the epoch
+fn _get_timestamp() -> f64 {
+    return SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or_default();
+}
+
+
+/// Returns the dir for the logs, it's in the user's cache dir, so other users can't tamper with them,
+/// None if the OS doesn't have a cache dir
+fn _get_log_dir() -> Option<PathBuf> {
+    return dirs::cache_dir().map(|dir| dir.join("mas-installer").join("logs"));
+}
+
+/// Returns the path to the log file, None if the OS doesn't have a cache dir
+pub fn get_log_file_path() -> Option<PathBuf> {
+    return _get_log_dir().map(|dir| dir.join(LOG_FILE_NAME));
+}
+
+/// Opens the log file for appending, creates it with its dir if needed,
+/// on unix only the user can read a new file
+fn _open_log_file(path: &Path) -> Result<File, io::Error> {
+    if let Some(dir) = path.parent() {
+        create_dir_all(dir)?;
+    }
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    return options.open(path);
+}
+
+/// Parses the log level from the env var, None if logging is disabled
+fn _get_level_filter() -> Option<LevelFilter> {
+    let value = env::var(LOG_LEVEL_ENV_VAR).ok()?;
+    return match value.trim().parse::<LevelFilter>() {
+        Ok(LevelFilter::Off) => None,
+        Ok(filter) => Some(filter),
+        Err(_) => {
+            eprintln!("Unknown log level in {LOG_LEVEL_ENV_VAR}: {value}, using debug");
+            Some(LevelFilter::Debug)
+        }
+    };
+}
+
+/// Sets up logging if it's enabled via the env var, should be called first thing
+pub fn init() {
+    let filter = match _get_level_filter() {
+        Some(filter) => filter,
+        None => return
+    };
+
+    let path = get_log_file_path();
+    match path.as_deref().map(_open_log_file) {
+        Some(Ok(file)) => *LOGGER.file.lock().unwrap() = Some(file),
+        Some(Err(e)) => eprintln!("Failed to open the log file at {}: {e}", path.as_deref().unwrap().display()),
+        None => eprintln!("There's no cache dir for the log file, only logging to stderr")
+    };
+
+    if let Err(e) = log::set_logger(&*LOGGER) {
+        eprintln!("Failed to set up logging: {e}");
+        return;
+    }
+    log::set_max_level(filter);
+    log::info!(
+        "Logging at {filter} level into {}, version {}",
+        path.as_deref().map_or("stderr".into(), Path::to_string_lossy),
+        crate::VERSION.unwrap_or(crate::DEF_VERSION)
+    );
+}
+
+
+/// Opens the install log for appending, creates it if needed
+fn _open_install_log(dir: &Path) -> Option<(PathBuf, File)> {
+    let path = dir.join(INSTALL_LOG_FILE_NAME);
+    return match _open_log_file(&path) {
+        Ok(file) => Some((path, file)),
+        Err(e) => {
+            eprintln!("Failed to open the install log at {}: {e}", path.display());
+            None
+        }
+    };
+}
+
+/// Starts a new session in the install log, returns the path to the log
+/// The log goes into the given dir, or our log dir if we can't write there
+/// NOTE: the log is appended to, so retries end up in the same file
+pub fn start_install_log(dir: &Path) -> Option<PathBuf> {
+    let mut install_log = INSTALL_LOG.lock().unwrap();
+
+    let is_same_dir = install_log.as_ref()
+        .map(|(path, _)| path.parent() == Some(dir))
+        .unwrap_or(false);
+    if !is_same_dir {
+        *install_log = _open_install_log(dir).or_else(|| _open_install_log(&_get_log_dir()?));
+    }
+
+    let (path, file) = install_log.as_mut()?;
+    writeln!(
+        file,
+        "[{:.3}] ===== Installer {} session =====",
+        _get_timestamp(),
+        crate::VERSION.unwrap_or(crate::DEF_VERSION)
+    ).ok();
+    return Some(path.clone());
+}
+
+/// Writes a line into the install log (and the debug log)
+pub fn install_log(msg: &str) {
+    log::info!("{msg}");
+    if let Some((_, file)) = INSTALL_LOG.lock().unwrap().as_mut() {
+        writeln!(file, "[{:.3}] {msg}", _get_timestamp()).ok();
+    }
+}
+
+/// Writes an error with all its sources into the install log
+pub fn install_log_error(error: &dyn StdError) {
+    install_log(&format!("Error: {error}"));
+    let mut source = error.source();
+    while let Some(e) = source {
+        install_log(&format!("    caused by: {e}"));
+        source = e.source();
+    }
+}
+
+/// Returns the path to the install log, None if it hasn't been started
+pub fn get_install_log_path() -> Option<PathBuf> {
+    return INSTALL_LOG.lock().unwrap().as_ref().map(|(path, _)| path.clone());
+}
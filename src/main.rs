@@ -3,12 +3,28 @@
 
 mod app;
 mod audio;
+mod cli;
+mod config;
 mod errors;
+mod logger;
+mod platform;
+mod reporter;
 mod static_data;
 mod utils;
 
 
-use errors::InstallerError;
+// Releases must ship the full license, not just a link to it
+#[cfg(all(not(debug_assertions), not(feature = "include_license")))]
+compile_error!("release builds need the include_license feature");
+
+use std::{
+    env,
+    process,
+    time::Duration
+};
+
+use errors::{InstallerError, ErrorKind};
+use app::strings::Language;
 
 
 // Get version from the cargo
@@ -19,31 +35,92 @@ const DEF_VERSION: &str = "unknown build";
 const ORG_NAME: &str = "Monika-After-Story";
 const REPO_NAME: &str = "MonikaModDev";
 
-// IDs of assets in github release
-const DEF_VERSION_ASSET_ID: usize = 1;
-const DLX_VERSION_ASSET_ID: usize = 0;
-const SPR_ASSET_ID: usize = 3;
+// Name patterns of assets in github release, '*' matches any sequence of characters
+const DEF_VERSION_ASSET_PATTERN: &str = "Monika_After_Story-*-Mod.zip";
+const DLX_VERSION_ASSET_PATTERN: &str = "Monika_After_Story-*-Mod-Dlx.zip";
+const SPR_ASSET_PATTERN: &str = "spritepacks-*.zip";
+// The bundle with all the spritepacks, installed unless the user picks others
+const SPR_DEF_BUNDLE: &str = "spritepacks-combined";
+
+// How many times we retry a failed request
+const DEF_MAX_RETRIES: u32 = 5;
+// How long we wait for a connection and for data on a stalled connection
+const DEF_CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+const DEF_READ_TIMEOUT: Duration = Duration::from_secs(60);
+// How long the install can go without any progress before we abort it
+const DEF_STALL_TIMEOUT: Duration = Duration::from_secs(120);
+// How much space the cached downloads can take, in MiB
+const DEF_CACHE_SIZE_MB: u64 = 2048;
 
 
 #[derive(Clone, Copy)]
 pub enum Message {
     UpdateProgressBar(f64),
+    ProgressIndeterminate,
+    RetryingDownload(u32),
+    Throttled(Duration),
+    DownloadSpeed(f64),
+    BytesProgress { done: u64, total: u64 },
+    Eta(Duration),
     Close,
+    WindowClose,
+    WorkerFinished,
     NextPage,
     PrevPage,
     SelectDir,
+    ReleasesLoaded,
+    ReleaseNotesLoaded,
+    VersionSelected,
     DlxVersionCheck,
     InstallSprCheck,
+    BackupCheck,
+    ShortcutCheck,
+    KeepArchivesCheck,
+    DryRunCheck,
+    PrereleaseCheck,
+    DarkThemeCheck,
+    LanguageSelected(Language),
+    ProxySettings,
+    SprBundles,
+    SpeedLimitChanged(Option<f64>),
     VolumeCheck,
     Install,
+    Uninstall,
+    SpritepacksOnly,
+    CopyDdlc,
+    Verify,
+    InstallFromFile,
+    About,
+    ViewLicense,
+    OpenFolder,
+    LaunchGame,
     Preparing,
+    FetchingReleaseInfo,
+    ReleaseResolved,
+    Copying,
+    BackingUp,
+    RemovingOldInstall,
     Downloading,
+    UsingCachedDownload,
     Extracting,
     DownloadingSpr,
     ExtractingSpr,
+    ExtractingFile,
+    Verifying,
+    ConfirmPopulatedDir,
+    ConfirmCreateDir,
+    ConfirmStaleFiles,
+    RemovingStaleFiles,
     CleaningUp,
-    Error,
+    DryRunReady,
+    VerifyReady,
+    Uninstalling,
+    Error(ErrorKind),
     Abort,
+    Cancel,
+    TogglePause,
+    EnterKey,
+    EscapeKey,
     Done
 }
 
@@ -52,12 +129,38 @@ type InstallResult = Result<(), InstallerError>;
 
 /// The entry point
 fn main() {
+    // Quiet unless enabled via the env var
+    logger::init();
+    // Warn once if the build lost the license
+    static_data::check_license();
+    // Find out early what this system can do, it gets logged
+    platform::get_capabilities();
+    // Scripted installs don't need any GUI
+    if cli::is_headless() {
+        let exit_code = match cli::parse_args(env::args().skip(1)) {
+            Ok(args) => cli::run_headless(args),
+            Err(e) => {
+                eprintln!("{e}\n\n{}", cli::USAGE);
+                2
+            }
+        };
+        process::exit(exit_code);
+    }
+
     // This needs to be done first
     utils::disable_global_hotkeys();
+    // Load the user preferences
+    let config = config::load_config();
     // Builds the app
-    let mut app = app::InstallerApp::default();
+    let mut app = app::InstallerApp::new(&config);
+    // Repeat users can skip the wizard
+    if (cli::is_compact() || app.get_compact_mode_flag()) && !app.enter_compact_mode() {
+        eprintln!("The saved directory isn't a valid DDLC directory, starting the full installer...");
+    }
     // Show it
     app.show();
     // Process events
     app.wait();
+    // Remember the preferences for the next run
+    app.save_config();
 }
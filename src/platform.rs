@@ -0,0 +1,273 @@
+/// The module that keeps the platform specific logic in one place,
+/// the rest of the installer asks it what the current system supports
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+    process::Command
+};
+
+
+// These filesystems are usually case-insensitive
+pub const IGNORE_CASE: bool = cfg!(any(target_os = "windows", target_os = "macos"));
+
+// Windows shells use %VAR% for env vars
+pub const PERCENT_ENV_VARS: bool = cfg!(target_os = "windows");
+
+// Icons the game might have, relative to the DDLC dir
+#[cfg(all(unix, not(target_os = "macos")))]
+const GAME_ICON_FILES: [&str; 2] = ["icon.png", "game/gui/window_icon.png"];
+
+
+/// Optional features that depend on the OS and the architecture
+#[derive(Debug, Clone, Copy)]
+pub struct Capabilities {
+    /// We can start the game after the install
+    pub launch_game: bool,
+    /// We can create a desktop shortcut to the game
+    pub shortcuts: bool,
+    /// We know where Steam keeps its libraries
+    pub steam_detection: bool
+}
+
+lazy_static::lazy_static! {
+    static ref CAPABILITIES: Capabilities = _detect_capabilities();
+}
+
+
+/// Checks what the current system supports
+fn _detect_capabilities() -> Capabilities {
+    // DDLC only ships x86 builds, Windows and MacOS can emulate them
+    let can_run_game = cfg!(any(target_os = "windows", target_os = "macos"))
+        || (cfg!(unix) && cfg!(any(target_arch = "x86", target_arch = "x86_64")));
+    let capabilities = Capabilities {
+        launch_game: can_run_game,
+        shortcuts: cfg!(any(windows, unix)) && dirs::desktop_dir().is_some(),
+        steam_detection: cfg!(any(target_os = "windows", target_os = "macos", target_os = "linux"))
+    };
+    log::debug!(
+        "Platform {} ({}): {:?}",
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        capabilities
+    );
+    return capabilities;
+}
+
+/// Returns the optional features available on this system, detected once
+pub fn get_capabilities() -> Capabilities {
+    return *CAPABILITIES;
+}
+
+/// Returns the error for a feature this system doesn't support
+pub fn unsupported_error(feature: &str) -> io::Error {
+    return io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("{feature} isn't supported on {} ({})", std::env::consts::OS, std::env::consts::ARCH)
+    );
+}
+
+/// Returns true if the OS error code means the disk is full
+pub fn is_disk_full_code(code: i32) -> bool {
+    // ENOSPC on unix, ERROR_HANDLE_DISK_FULL and ERROR_DISK_FULL on Windows
+    let codes: &[i32] = match cfg!(target_os = "windows") {
+        true => &[39, 112],
+        false => &[28]
+    };
+    return codes.contains(&code);
+}
+
+/// Returns the canonical form of the given existing path
+/// NOTE: on Windows this drops the \\?\ prefix from plain drive paths,
+/// some programs (and users) don't understand it
+pub fn canonicalize(path: &Path) -> Result<PathBuf, io::Error> {
+    let rv = path.canonicalize()?;
+    if cfg!(target_os = "windows") {
+        if let Some(stripped) = rv.to_str().and_then(|s| s.strip_prefix(r"\\?\")) {
+            if !stripped.starts_with(r"UNC\") {
+                return Ok(PathBuf::from(stripped));
+            }
+        }
+    }
+    return Ok(rv);
+}
+
+/// Returns the program used to open files/dirs on this platform
+pub fn get_opener() -> &'static str {
+    if cfg!(target_os = "windows") {
+        return "explorer";
+    }
+    if cfg!(target_os = "macos") {
+        return "open";
+    }
+    return "xdg-open";
+}
+
+/// Returns the default Steam install dirs on this platform
+pub fn get_steam_dirs() -> Vec<PathBuf> {
+    if !get_capabilities().steam_detection {
+        return Vec::new();
+    }
+    if cfg!(target_os = "windows") {
+        return vec![
+            PathBuf::from(r"C:\Program Files (x86)\Steam"),
+            PathBuf::from(r"C:\Program Files\Steam")
+        ];
+    }
+
+    let home = match dirs::home_dir() {
+        Some(home) => home,
+        None => return Vec::new()
+    };
+    if cfg!(target_os = "macos") {
+        return vec![home.join("Library/Application Support/Steam")];
+    }
+    return vec![
+        home.join(".steam/steam"),
+        home.join(".local/share/Steam"),
+        // Flatpak
+        home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam")
+    ];
+}
+
+/// Returns the path to the game executable in the given DDLC dir
+/// (the app bundle on MacOS)
+pub fn get_game_executable(path: &Path) -> PathBuf {
+    if cfg!(target_os = "windows") {
+        return path.join("DDLC.exe");
+    }
+    if cfg!(target_os = "macos") {
+        return path.join("DDLC.app");
+    }
+    return path.join("DDLC.sh");
+}
+
+/// Returns the command that starts the given game executable
+pub fn build_launch_command(executable: &Path) -> Command {
+    if cfg!(target_os = "windows") {
+        return Command::new(executable);
+    }
+    // The launcher script might not have the exec permission,
+    // and app bundles have to be launched through the opener
+    let mut cmd = match cfg!(target_os = "macos") {
+        true => Command::new(get_opener()),
+        false => Command::new("sh")
+    };
+    cmd.arg(executable);
+    return cmd;
+}
+
+/// Returns the icon for the shortcut, the game's own if it has one,
+/// otherwise the installer's icon is saved into the data dir
+#[cfg(all(unix, not(target_os = "macos")))]
+fn _get_shortcut_icon(game_dir: &Path) -> Result<PathBuf, io::Error> {
+    use std::fs::{create_dir_all, write as write_file};
+
+    if let Some(icon) = GAME_ICON_FILES.iter().map(|name| game_dir.join(name)).find(|path| path.is_file()) {
+        return Ok(icon);
+    }
+    let icon = dirs::data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("mas-installer")
+        .join("icon.png");
+    if !icon.exists() {
+        create_dir_all(icon.parent().unwrap())?;
+        write_file(&icon, crate::static_data::APP_ICON_DATA)?;
+    }
+    return Ok(icon);
+}
+
+/// Creates a desktop shortcut to the target with the given name,
+/// returns the path to the shortcut
+/// NOTE: a .lnk on Windows, an alias on MacOS (it uses the target's icon)
+/// and a .desktop file on other unix systems
+pub fn create_shortcut(target: &Path, name: &str) -> Result<PathBuf, io::Error> {
+    if !get_capabilities().shortcuts {
+        return Err(unsupported_error("creating shortcuts"));
+    }
+    let desktop = dirs::desktop_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "couldn't find the desktop folder"))?;
+    if !target.exists() {
+        return Err(
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("couldn't find the shortcut target {}", target.display())
+            )
+        );
+    }
+    let working_dir = target.parent().unwrap_or(target);
+
+    #[cfg(target_os = "windows")]
+    {
+        // There's no API for .lnk files in std, so we ask the shell to make one,
+        // the icon is taken from the game executable
+        let path = desktop.join(format!("{name}.lnk"));
+        let quote = |path: &Path| format!("'{}'", path.display().to_string().replace('\'', "''"));
+        let script = format!(
+            "$s = (New-Object -ComObject WScript.Shell).CreateShortcut({}); \
+            $s.TargetPath = {}; $s.WorkingDirectory = {}; $s.IconLocation = {}; $s.Save()",
+            quote(&path),
+            quote(target),
+            quote(working_dir),
+            quote(target)
+        );
+        let status = Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+            .status()?;
+        if !status.success() {
+            return Err(io::Error::new(io::ErrorKind::Other, format!("powershell exited with {status}")));
+        }
+        return Ok(path);
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let _ = working_dir;
+        let path = desktop.join(name);
+        let quote = |path: &Path| format!("\"{}\"", path.display().to_string().replace('\\', "\\\\").replace('"', "\\\""));
+        let script = format!(
+            "tell application \"Finder\" to make alias file to (POSIX file {}) at (POSIX file {}) with properties {{name:{}}}",
+            quote(target),
+            quote(&desktop),
+            quote(Path::new(name))
+        );
+        let status = Command::new("osascript").args(["-e", &script]).status()?;
+        if !status.success() {
+            return Err(io::Error::new(io::ErrorKind::Other, format!("osascript exited with {status}")));
+        }
+        return Ok(path);
+    }
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        use std::fs::{set_permissions, write as write_file, Permissions};
+        use std::os::unix::fs::PermissionsExt;
+
+        // See the desktop entry spec for the quoting rules of Exec
+        let quote_exec = |path: &Path| -> String {
+            let escaped: String = path.display().to_string().chars()
+                .flat_map(|c| match c {
+                    '"' | '`' | '$' | '\\' => vec!['\\', '\\', c],
+                    _ => vec![c]
+                })
+                .collect();
+            return format!("\"{escaped}\"");
+        };
+        let icon = _get_shortcut_icon(working_dir)?;
+        let path = desktop.join(format!("{name}.desktop"));
+        // The launcher script might not have the exec permission
+        let content = format!(
+            "[Desktop Entry]\nType=Application\nName={name}\nExec=sh {}\nPath={}\nIcon={}\nTerminal=false\n",
+            quote_exec(target),
+            working_dir.display(),
+            icon.display()
+        );
+        write_file(&path, content)?;
+        // Some desktops only launch executable entries
+        set_permissions(&path, Permissions::from_mode(0o755))?;
+        return Ok(path);
+    }
+    #[cfg(not(any(windows, unix)))]
+    {
+        let _ = (desktop, working_dir, name);
+        return Err(unsupported_error("creating shortcuts"));
+    }
+}
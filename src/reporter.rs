@@ -0,0 +1,45 @@
+/// The module with the interface the installer reports its progress through,
+/// the GUI implements it with the FLTK channel, the headless mode prints to stdout
+
+use std::sync::Arc;
+
+use fltk::app::Sender;
+
+use crate::{
+    errors::ErrorKind,
+    Message
+};
+
+
+/// Receives the progress of the installation,
+/// so the install logic doesn't depend on the front-end
+pub trait ProgressReporter: Send + Sync {
+    /// Reports any message, the other methods go through this by default
+    fn report(&self, msg: Message);
+
+    /// The installer moved onto another phase, e.g. Message::Downloading
+    fn phase(&self, msg: Message) {
+        self.report(msg);
+    }
+
+    /// The total progress of the installation, from 0 to 1
+    fn progress(&self, value: f64) {
+        self.report(Message::UpdateProgressBar(value));
+    }
+
+    /// The installation has failed
+    fn error(&self, kind: ErrorKind) {
+        self.report(Message::Error(kind));
+    }
+}
+
+/// Reporter shared between the installer threads
+pub type Reporter = Arc<dyn ProgressReporter>;
+
+
+/// The GUI gets the messages through the FLTK channel
+impl ProgressReporter for Sender<Message> {
+    fn report(&self, msg: Message) {
+        self.send(msg);
+    }
+}
@@ -8,6 +8,8 @@ pub static APP_ICON_DATA: &'static [u8] = include_bytes!("static/icon.png");
 pub static APP_LICENSE: &'static str = include_str!("static/license.md");
 #[cfg(not(feature="include_license"))]
 pub static APP_LICENSE: &'static str = "You can find the license at https://github.com/Monika-After-Story/MonikaModDev/blob/master/LICENSE.md";
+/// Whether APP_LICENSE is the full license or just a link to it
+pub const LICENSE_INCLUDED: bool = cfg!(feature="include_license");
 
 // Images
 pub static VERTICAL_BAR_DATA: &'static [u8] = include_bytes!("static/vertical_bar.png");
@@ -18,3 +20,18 @@ pub static VOLUME_BUT_HOVER_DATA: &'static [u8] = include_bytes!("static/but_vol
 // Sounds
 // Credits: Ludum Dare 28 - Tracks 1 and 3 by @ben_burnes http://abstractionmusic.bandcamp.com/
 pub static INSTALLER_THEME_DATA: &'static [u8] = include_bytes!("static/ludum_dare_28_track_1.ogg");
+
+
+/// Checks that the license got bundled, logs a warning if it didn't
+/// returns true if APP_LICENSE is the full license, called once at startup
+pub fn check_license() -> bool {
+    if !LICENSE_INCLUDED {
+        log::warn!("Built without the include_license feature, only showing a link to the license");
+        return false;
+    }
+    if APP_LICENSE.trim().is_empty() {
+        log::warn!("The bundled license is empty");
+        return false;
+    }
+    return true;
+}